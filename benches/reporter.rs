@@ -0,0 +1,107 @@
+//! Benchmarks for the reporting hot paths.
+//!
+//! ```text
+//! cargo bench --bench reporter
+//! cargo bench --bench reporter --features async
+//! ```
+//!
+//! Every reporter is a process-wide singleton that can only be initialized
+//! once, so each benchmark gets its own reporter and the collector keeps every
+//! error reported over the course of the run.
+
+use {
+    criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput},
+    error_report::PausePolicy,
+    std::time::{Duration, Instant},
+};
+
+error_report::make_reporter!(Latency, thread = LatencyThread, macro = latency);
+error_report::make_reporter!(Contended, thread = ContendedThread, macro = contended);
+error_report::make_reporter!(Updated<u64>, thread = UpdatedThread, macro = updated);
+error_report::make_reporter!(Drained, thread = DrainedThread, macro = drained);
+
+fn report_latency(c: &mut Criterion) {
+    let mut et = LatencyThread::default();
+    Latency::init(&mut et);
+
+    c.bench_function("report", |b| b.iter(|| latency!("benchmark error")));
+}
+
+fn multithread_throughput(c: &mut Criterion) {
+    let mut et = ContendedThread::default();
+    Contended::init(&mut et);
+
+    const PER_THREAD: u64 = 1000;
+    let mut group = c.benchmark_group("throughput");
+    for threads in [1, 4, 16] {
+        group.throughput(Throughput::Elements(threads * PER_THREAD));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    std::thread::scope(|scope| {
+                        for _ in 0..threads {
+                            scope.spawn(|| {
+                                for _ in 0..PER_THREAD {
+                                    contended!("benchmark error");
+                                }
+                            });
+                        }
+                    });
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn update_cost(c: &mut Criterion) {
+    let mut et = UpdatedThread::default();
+    Updated::init(&mut et);
+
+    let key = updated!("benchmark error");
+    let mut extra = 0;
+    c.bench_function("update", |b| {
+        b.iter(|| {
+            extra += 1;
+            Updated::update_detached(key, extra);
+        })
+    });
+}
+
+fn drain_time(c: &mut Criterion) {
+    let mut et = DrainedThread::default();
+    Drained::init(&mut et);
+
+    // done() can only be called once per process, so instead time resuming collection, which
+    // stores the buffered errors the same way done() does when the collector finishes
+    c.bench_function("drain", |b| {
+        b.iter_custom(|iters| {
+            Drained::pause(PausePolicy::Buffer);
+            Drained::report_batch((0..iters).map(|_| anyhow::anyhow!("benchmark error")));
+            let start = Instant::now();
+            Drained::resume();
+            Drained::count();
+            start.elapsed()
+        })
+    });
+
+    // and done() itself, once
+    Drained::pause(PausePolicy::Buffer);
+    Drained::report_batch((0..100_000).map(|_| anyhow::anyhow!("benchmark error")));
+    let start = Instant::now();
+    let errors = et.done();
+    println!(
+        "done: stored {} errors in {:?}",
+        errors.len(),
+        start.elapsed()
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().measurement_time(Duration::from_secs(3));
+    targets = report_latency, multithread_throughput, update_cost, drain_time
+}
+criterion_main!(benches);
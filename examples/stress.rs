@@ -0,0 +1,143 @@
+//! Stress the error collector and measure how it keeps up.
+//!
+//! ```text
+//! cargo run --release --example stress -- --threads 100 --errors-per-thread 5000
+//! ```
+
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: stress [options]
+
+options:
+    --threads <n>            number of reporting threads (default 100)
+    --errors-per-thread <n>  errors reported by each thread (default 5000)
+    --payload-size <bytes>   size of the payload in each error message (default 16)
+    --update-ratio <ratio>   fraction of errors which are updated after being reported (default 0)";
+
+error_report::make_reporter!(Stress<String>);
+
+struct Options {
+    threads: usize,
+    errors_per_thread: usize,
+    payload_size: usize,
+    update_ratio: f64,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        threads: 100,
+        errors_per_thread: 5000,
+        payload_size: 16,
+        update_ratio: 0.0,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--threads" => options.threads = parse(&arg, &value)?,
+            "--errors-per-thread" => options.errors_per_thread = parse(&arg, &value)?,
+            "--payload-size" => options.payload_size = parse(&arg, &value)?,
+            "--update-ratio" => options.update_ratio = parse(&arg, &value)?,
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+
+    if options.threads == 0 || options.errors_per_thread == 0 {
+        return Err(String::from("at least one error must be reported"));
+    }
+    if !(0.0..=1.0).contains(&options.update_ratio) {
+        return Err(String::from("--update-ratio must be between 0 and 1"));
+    }
+
+    Ok(options)
+}
+
+fn parse<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {arg}: {value}"))
+}
+
+/// Get the latency at a percentile of a sorted list of latencies.
+fn percentile(latencies: &[Duration], p: f64) -> Duration {
+    let index = ((latencies.len() - 1) as f64 * p / 100.0).round() as usize;
+    latencies[index]
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut et = ErrorThread::default();
+    Stress::init(&mut et);
+
+    let start = Instant::now();
+    let threads = (0..options.threads)
+        .map(|thread| {
+            let Options {
+                errors_per_thread,
+                payload_size,
+                update_ratio,
+                ..
+            } = options;
+
+            std::thread::spawn(move || {
+                let payload = "x".repeat(payload_size);
+                let mut latencies = Vec::with_capacity(errors_per_thread);
+                for i in 0..errors_per_thread {
+                    let reported = Instant::now();
+                    let key = report!("thread {thread} error {i}: {payload}");
+                    latencies.push(reported.elapsed());
+
+                    // update exactly update_ratio of the errors, spread evenly
+                    let updates = |i: usize| (i as f64 * update_ratio).floor();
+                    if updates(i + 1) > updates(i) {
+                        Stress::update_detached(key, payload.clone());
+                    }
+                }
+                latencies
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut latencies = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+    let reported = start.elapsed();
+
+    let errors = et.done();
+    let drained = start.elapsed();
+
+    assert_eq!(errors.len(), options.threads * options.errors_per_thread);
+    let updated = errors
+        .values()
+        .filter(|error| error.extra().is_some())
+        .count();
+
+    latencies.sort();
+    let throughput = errors.len() as f64 / drained.as_secs_f64();
+
+    println!(
+        "{} errors ({updated} updated) from {} threads",
+        errors.len(),
+        options.threads
+    );
+    println!("reported in {reported:?}, drained in {drained:?}");
+    println!("throughput: {throughput:.0} errors/s");
+    println!(
+        "report latency: p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        percentile(&latencies, 50.0),
+        percentile(&latencies, 90.0),
+        percentile(&latencies, 99.0),
+        latencies.last().unwrap(),
+    );
+}
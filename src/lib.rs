@@ -46,140 +46,128 @@
 //! }
 //! ```
 
+use {
+    slotmap::{DefaultKey, Key},
+    std::{
+        any::Any,
+        io::{self, Read, Write},
+        marker::PhantomData,
+        process::Command,
+    },
+};
+
 /// Macro to create error reporting infrastructure.
 ///
 /// See [example::ExampleReporter] for the generated API.
+///
+/// # Options
+///
+/// By default the macro introduces `$ErrorName`, `ErrorThread`, and `report!` to the namespace.
+/// The names of the generated items may be changed with trailing options, which allows more than
+/// one reporter to live in the same module:
+///
+/// - `thread = Name` names the error collector thread type instead of `ErrorThread`.
+/// - `macro = name` names the report macro instead of `report!`.
+/// - `key = Name` additionally introduces a type alias for the key type, [slotmap::DefaultKey].
+///
+/// ```
+/// error_report::make_reporter!(NetworkError<String>, thread = NetworkThread, macro = report_net);
+/// error_report::make_reporter!(PipelineError, thread = PipelineThread, macro = report_pipeline, key = PipelineKey);
+///
+/// let mut network = NetworkThread::default();
+/// NetworkError::init(&mut network);
+/// let mut pipeline = PipelineThread::default();
+/// PipelineError::init(&mut pipeline);
+///
+/// report_net!("connection reset");
+/// let key: PipelineKey = report_pipeline!("bad record");
+///
+/// assert_eq!(1, network.done().len());
+/// assert!(pipeline.done().contains_key(key));
+/// ```
+///
+/// # Thread safety
+///
+/// Errors, their extra information, and the functions passed to `for_each` are moved to the error
+/// collector thread, which outlives any scope it is used from. The extra type and those functions
+/// must therefore be [Send] and `'static`, and the reporter may be used freely from
+/// [scoped threads](std::thread::scope) as long as nothing borrowed from the scope is sent. To get
+/// results out of a `for_each`, send them over a channel or share them with an
+/// [Arc](std::sync::Arc).
+///
+/// ```compile_fail
+/// // Rc is not Send, so it cannot be used as extra information
+/// error_report::make_reporter!(DocTest<std::rc::Rc<String>>);
+/// ```
+///
+/// # Derives
+///
+/// The generated error type always implements [Debug], [Display](std::fmt::Display), and
+/// [std::error::Error]. Its `Display` output includes the whole chain of messages, so it has no
+/// `source`. Additional impls may be requested with a trailing `derive(...)` option, as
+/// long as the extra type supports them:
+///
+/// - `Clone` clones the error as its rendered chain of messages, so the clone can no longer be
+///   downcast to the original error type. The backtrace, if any, is not cloned.
+/// - `PartialEq` compares the rendered chain of messages, every extra, and the severity.
+/// - `Serialize` serializes the error as its rendered chain of messages along with every extra and
+///   the name of its severity. Requires `serde` to be a dependency of the calling crate.
+/// - `ReporterExtra` implements [ReporterExtra], which is needed by [Reported::update],
+///   [ResultExt::with_report_context], [handle], and [registry]. Requires the extra type to be
+///   public, since the trait names it.
+///
+/// ```
+/// error_report::make_reporter!(DocTest<String>, derive(Clone, PartialEq));
+/// ```
 #[macro_export]
 macro_rules! make_reporter {
-    ($ErrorName:ident) => {
-        $crate::make_reporter!($ErrorName<()>);
+    // pass a `$` along, so the generated macro can use repetitions of its own
+    (@options $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, $Key:tt, $derive:tt]) => {
+        $crate::make_reporter!(@generate ($) $ErrorName<$T> [$Thread, $report, $Key, $derive]);
     };
 
-    ($ErrorName:ident < $T:ty >) => {
-        use {
-            anyhow::Error,
-            flume::{Receiver, RecvError, Sender},
-            once_cell::sync::OnceCell,
-            slotmap::{DefaultKey, SlotMap},
-            std::thread::JoinHandle,
-        };
-
-        /// The [Sender] responsible for sending [Message]s to the error collector thread.
-        static MSG_TX: OnceCell<Sender<Message>> = OnceCell::new();
-
+    (@generate ($d:tt) $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, [$($Key:ident)?], [$($derive:ident)*]]) => {
         /// The error type for this reporter.
         #[derive(Debug)]
         pub struct $ErrorName {
-            error: Error,
-            extra: Option<$T>,
+            error: anyhow::Error,
+            extras: Vec<$T>,
+            retry: Option<$crate::Retry>,
+            reported_at: std::time::SystemTime,
+            reported: std::time::Instant,
+            severity: $crate::Severity,
+            thread_name: Option<String>,
+            thread_id: std::thread::ThreadId,
+            location: &'static std::panic::Location<'static>,
+            backtrace: Option<std::backtrace::Backtrace>,
+            parent: Option<slotmap::DefaultKey>,
         }
 
-        impl $ErrorName {
-            /// Get the underlying [anyhow::Error].
-            pub fn error(&self) -> &Error {
-                &self.error
-            }
-
-            /// Get the extra information, if any.
-            pub fn extra(&self) -> Option<&$T> {
-                self.extra.as_ref()
-            }
-
-            pub fn error_mut(&mut self) -> &mut Error {
-                &mut self.error
-            }
-
-            pub fn extra_mut(&mut self) -> Option<&mut $T> {
-                self.extra.as_mut()
-            }
-
-            /// Initialize the error collector thread.
-            ///
-            /// This is done as a non-associated function on [ErrorThread] to require the user to
-            /// not discard the [ErrorThread] prematurely. This is important as its [Drop]
-            /// implementation quits the error collector thread, dropping the [Receiver] and thus
-            /// causing any subsequent error reports to panic.
-            ///
-            /// # Panics
-            ///
-            /// The function must not already have been called.
-            ///
-            /// # Examples
-            ///
-            /// ```
-            /// error_report::make_reporter!(DocTest);
-            /// let mut et = ErrorThread::default();
-            /// DocTest::init(&mut et);
-            /// ```
-            pub fn init(error_thread: &mut ErrorThread) {
-                let (message_tx, message_rx) = flume::unbounded();
-                MSG_TX.set(message_tx).expect(INIT_MSG);
-
-                let handle = std::thread::spawn(|| handle_messages(message_rx));
-
-                error_thread.handle = Some(handle);
-            }
-
-            /// Report an error.
-            ///
-            /// See also [report!].
-            ///
-            /// # Panics
-            ///
-            #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-            /// must have been called and [ErrorThread::done] must not have been called.
-            pub fn report(error: Error) -> DefaultKey {
-                let msg_tx = MSG_TX.get().expect(INIT_MSG);
-                let (key_tx, key_rx) = flume::bounded(1);
-                msg_tx.send(Message::Error(error, key_tx)).expect(INIT_MSG);
-                key_rx.recv().expect(INIT_MSG)
-            }
-
-            /// Update an error with additional information.
-            ///
-            /// # Panics
-            ///
-            #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-            /// must have been called and [ErrorThread::done] must not have been called.
-            pub fn update(key: DefaultKey, extra: $T) {
-                let msg_tx = MSG_TX.get().expect(INIT_MSG);
-                msg_tx.send(Message::Update(key, extra)).expect(INIT_MSG);
-            }
-
-            /// Execute a function for each error.
-            ///
-            /// # Panics
-            ///
-            #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-            /// must have been called and [ErrorThread::done] must not have been called.
-            pub fn for_each(f: impl FnMut(&$ErrorName) + 'static) {
-                let msg_tx = MSG_TX.get().expect(INIT_MSG);
-                msg_tx.send(Message::ForEach(Box::new(f))).expect(INIT_MSG);
-            }
-
-            /// Execute a function for each error, mutably.
-            ///
-            /// # Panics
-            ///
-            #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-            /// must have been called and [ErrorThread::done] must not have been called.
-            pub fn for_each_mut(f: impl FnMut(&mut $ErrorName) + 'static) {
-                let msg_tx = MSG_TX.get().expect(INIT_MSG);
-                msg_tx.send(Message::ForEachMut(Box::new(f))).expect(INIT_MSG);
-            }
+        /// The error collector thread.
+        ///
+        /// A newtype wrapping [std::thread::JoinHandle]. Its [Drop] implementation stops the error
+        /// collector thread, meaning any library calls afterward will panic.
+        #[derive(Default)]
+        pub struct $Thread {
+            handle: Option<std::thread::JoinHandle<slotmap::SlotMap<slotmap::DefaultKey, $ErrorName>>>,
         }
 
+        $(
+            /// The key type for errors reported with this reporter.
+            pub type $Key = slotmap::DefaultKey;
+        )?
+
         /// Report an error.
         ///
-        /// This macro is a thin shim around [anyhow::anyhow!]. Requires
+        /// This macro is a thin shim around [anyhow::anyhow!], and accepts the same arguments: a
+        /// format string with its arguments, or an error value. Errors reported before
         #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-        /// to have been called.
+        /// is called are buffered until it is.
         ///
         /// # Panics
         ///
         /// This macro will panic at runtime if
-        #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-        /// has not been called or [ErrorThread::done] has been called.
+        #[doc = concat!("[", stringify!($Thread), "::done] has been called.")]
         ///
         /// # Examples
         ///
@@ -192,128 +180,3423 @@ macro_rules! make_reporter {
         /// let why = "something heinous";
         /// DocTest::update(key, format!("this is why: {why}"));
         /// ```
+        ///
+        /// Format strings and error values work like they do with [anyhow::anyhow!]:
+        ///
+        /// ```
+        /// error_report::make_reporter!(DocTest);
+        /// let mut et = ErrorThread::default();
+        /// DocTest::init(&mut et);
+        /// let path = "config.toml";
+        /// let literal = report!("dang");
+        /// let inline = report!("failed to open {path}");
+        /// let positional = report!("failed to open {}: {}", path, "not found");
+        /// let value = report!(std::fmt::Error);
+        /// let errors = et.done();
+        /// assert_eq!("dang", errors[literal].error().to_string());
+        /// assert_eq!("failed to open config.toml", errors[inline].error().to_string());
+        /// assert_eq!("failed to open config.toml: not found", errors[positional].error().to_string());
+        /// assert_eq!("an error occurred when formatting an argument", errors[value].error().to_string());
+        /// ```
+        ///
+        /// Extra information that is already known may be attached immediately:
+        ///
+        /// ```
+        /// error_report::make_reporter!(DocTest<String>);
+        /// let mut et = ErrorThread::default();
+        /// DocTest::init(&mut et);
+        /// let why = "something heinous";
+        /// let key = report!("dang: {why}", extra: String::from("more information"));
+        /// let positional = report!("dang: {}", why, extra: String::from("more information"));
+        /// let errors = et.done();
+        /// assert_eq!("dang: something heinous", errors[key].error().to_string());
+        /// assert!(errors[key].extra().is_some());
+        /// assert_eq!("dang: something heinous", errors[positional].error().to_string());
+        /// ```
+        ///
+        /// With `try:`, the macro does not panic, and returns the result of
+        #[doc = concat!("[", stringify!($ErrorName), "::try_report].")]
+        ///
+        /// With `parent: key,` the error is reported as a child of another with
+        #[doc = concat!("[", stringify!($ErrorName), "::report_child], or with [", stringify!($ErrorName), "::report_child_with_extra] if `extra:` is also given.")]
         #[macro_export]
-        macro_rules! report {
-            ($e:expr) => {
+        macro_rules! $report {
+            (try: $d($d arg:tt)+) => {
+                $ErrorName::try_report(anyhow::anyhow!($d($d arg)+))
+            };
+
+            (parent: $parent:expr, $fmt:literal $d(, $d arg:expr)* $d(,)?) => {
+                $ErrorName::report_child($parent, anyhow::anyhow!($fmt $d(, $d arg)*))
+            };
+
+            (parent: $parent:expr, $e:expr $d(,)?) => {
+                $ErrorName::report_child($parent, anyhow::anyhow!($e))
+            };
+
+            (parent: $parent:expr, $d($d arg:tt)+) => {
+                $report!(@extra [$parent] [] $d($d arg)+)
+            };
+
+            ($fmt:literal $d(, $d arg:expr)* $d(,)?) => {
+                $ErrorName::report(anyhow::anyhow!($fmt $d(, $d arg)*))
+            };
+
+            ($e:expr $d(,)?) => {
                 $ErrorName::report(anyhow::anyhow!($e))
             };
-        }
 
-        /// The message which appears when the library is misused.
-        pub const INIT_MSG: &'static str = "init() should be called once, and its result not discarded.\nlet errors = error_report::init(); // do not assign to _, you must include a name";
+            // matching format arguments as expressions followed by `, extra: ...` is ambiguous, so
+            // when `extra:` is given, collect the arguments for anyhow::anyhow! one token at a time
+            // until only `, extra: ...` or nothing is left
+            (@extra [] [$d($d arg:tt)+] , extra: $extra:expr $d(,)?) => {
+                $ErrorName::report_with_extra(anyhow::anyhow!($d($d arg)+), $extra)
+            };
 
-        /// Message types that the library may send to the error collector thread.
-        enum Message {
-            /// An error that is reported.
-            ///
-            /// Requires a sender to be send along with it so that the error reporting thread may reply
-            /// with the slotmap's key.
-            Error(Error, Sender<DefaultKey>),
+            (@extra [$parent:expr] [$d($d arg:tt)+] , extra: $extra:expr $d(,)?) => {
+                $ErrorName::report_child_with_extra($parent, anyhow::anyhow!($d($d arg)+), $extra)
+            };
 
-            /// Update an error.
-            Update(DefaultKey, $T),
+            (@extra [] [$d($d arg:tt)+]) => {
+                $ErrorName::report(anyhow::anyhow!($d($d arg)+))
+            };
 
-            /// Execute a function for each error.
-            ForEach(Box<dyn FnMut(&$ErrorName)>),
+            (@extra [$parent:expr] [$d($d arg:tt)+]) => {
+                $ErrorName::report_child($parent, anyhow::anyhow!($d($d arg)+))
+            };
 
-            /// Execute a function for each error, mutably.
-            ForEachMut(Box<dyn FnMut(&mut $ErrorName)>),
+            (@extra $parent:tt [$d($d arg:tt)*] $next:tt $d($d rest:tt)*) => {
+                $report!(@extra $parent [$d($d arg)* $next] $d($d rest)*)
+            };
 
-            /// Exit the error collector thread.
-            ///
-            /// This is necessary because we hold onto a static [Sender], so the channel will never be
-            /// closed under normal circumstances.
-            Quit,
+            ($d($d arg:tt)+) => {
+                $report!(@extra [] [] $d($d arg)+)
+            };
         }
 
-        impl std::fmt::Debug for Message {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                match self {
-                    Message::Error(err, _) => write!(f, "Error({err:?})"),
-                    Message::Update(_, s) => write!(f, "Update({s:?})"),
-                    Message::ForEach(_) => write!(f, "ForEach(...)"),
-                    Message::ForEachMut(_) => write!(f, "ForEachMut(...)"),
-                    Message::Quit => write!(f, "Quit"),
-                }
-            }
-        }
+        $($crate::__reporter_derive!($derive, $ErrorName<$T>);)*
 
-        unsafe impl Sync for Message {}
-        unsafe impl Send for Message {}
+        const _: () = {
+            use {
+                anyhow::Error,
+                flume::{Receiver, RecvError, RecvTimeoutError, Sender},
+                once_cell::sync::OnceCell,
+                slotmap::{DefaultKey, SlotMap},
+                slotmap::Key,
+                std::{
+                    collections::{BTreeSet, HashMap},
+                    sync::{
+                        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+                        Mutex, PoisonError,
+                    },
+                    panic::Location,
+                    thread::ThreadId,
+                    time::{Duration, Instant, SystemTime},
+                },
+                $crate::{CollectorState, EarlyExitGuard, ErrorSnapshot, Partition, PausePolicy, ReportError, Severity, SeverityCounts, Watermark, INIT_MSG, PRE_INIT_CAPACITY, REENTRANT_MSG},
+            };
 
-        /// The error collector thread.
-        ///
-        /// A newtype wrapping [std::thread::JoinHandle]. Its [Drop] implementation stops the error
-        /// collector thread, meaning any library calls afterward will panic.
-        #[derive(Default)]
-        pub struct ErrorThread {
-            handle: Option<JoinHandle<SlotMap<DefaultKey, $ErrorName>>>,
-        }
+            /// The [Sender] responsible for sending [Message]s to the error collector thread.
+            static MSG_TX: OnceCell<Sender<Message>> = OnceCell::new();
 
-        impl ErrorThread {
-            /// Get the final list of errors.
-            ///
-            /// There should be no more calls to library functions after this call.
-            ///
-            /// # Panics
-            ///
-            /// Panics if
-            #[doc = concat!("[", stringify!($ErrorName), "::init]")]
-            /// has not been called.
-            pub fn done(mut self) -> SlotMap<DefaultKey, $ErrorName> {
-                let tx = MSG_TX.get().expect(INIT_MSG);
-                tx.send(Message::Quit).expect(INIT_MSG);
-                self.handle.take().expect(INIT_MSG).join().unwrap()
-            }
-        }
+            /// Errors reported before the error collector thread was started.
+            static PENDING: Mutex<Vec<$ErrorName>> = Mutex::new(Vec::new());
 
-        impl Drop for ErrorThread {
-            fn drop(&mut self) {
-                let tx = MSG_TX.get().expect(INIT_MSG);
-                let _x = tx.send(Message::Quit);
+            /// The queue depths at which to notify the application of backpressure.
+            static WATERMARKS: OnceCell<Watermarks> = OnceCell::new();
+
+            /// Whether the queue depth has crossed the high watermark without yet falling back to
+            /// the low watermark.
+            static ABOVE_HIGH: AtomicBool = AtomicBool::new(false);
+
+            struct Watermarks {
+                high: usize,
+                low: usize,
+                callback: Box<dyn Fn(Watermark, usize) + Send + Sync>,
             }
-        }
 
-        fn handle_messages(message_rx: Receiver<Message>) -> SlotMap<DefaultKey, $ErrorName> {
-            let mut errors = SlotMap::new();
+            /// The number of errors discarded while collection was paused.
+            static DISCARDED: AtomicUsize = AtomicUsize::new(0);
+
+            /// The number of [Message]s the error collector thread has finished processing.
+            static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+            /// The number of errors currently stored by the error collector thread.
+            static STORED: AtomicUsize = AtomicUsize::new(0);
+
+            /// The number of errors dropped because the pre-init buffer was full, they were less severe
+            /// than the minimum, they were filtered out by middleware, or they were discarded while
+            /// collection was paused.
+            static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+            /// The number of errors evicted because they were older than the retention window.
+            static EVICTED: AtomicUsize = AtomicUsize::new(0);
+
+            /// The error collector thread, which must not wait for replies from itself.
+            static COLLECTOR_THREAD: OnceCell<ThreadId> = OnceCell::new();
+
+            /// When the error collector thread was started.
+            static STARTED: OnceCell<Instant> = OnceCell::new();
+
+            /// What the error collector thread is currently doing.
+            static STATE: Mutex<CollectorState> = Mutex::new(CollectorState::Running);
+
+            /// When the error collector thread last finished processing a [Message], in nanoseconds
+            /// since the Unix epoch, or zero if it has not processed any.
+            static LAST_PROCESSED: AtomicU64 = AtomicU64::new(0);
+
+            impl $ErrorName {
+                /// Get the underlying [anyhow::Error].
+                pub fn error(&self) -> &Error {
+                    &self.error
+                }
+
+                /// Get the most recent extra information, if any.
+                pub fn extra(&self) -> Option<&$T> {
+                    self.extras.last()
+                }
+
+                /// Get every piece of extra information, oldest first.
+                pub fn extras(&self) -> &[$T] {
+                    &self.extras
+                }
+
+                pub fn error_mut(&mut self) -> &mut Error {
+                    &mut self.error
+                }
+
+                pub fn extra_mut(&mut self) -> Option<&mut $T> {
+                    self.extras.last_mut()
+                }
+
+                /// Get the retry information, if any.
+                pub fn retry(&self) -> Option<&$crate::Retry> {
+                    self.retry.as_ref()
+                }
+
+                /// Get the time the error was reported.
+                pub fn reported_at(&self) -> SystemTime {
+                    self.reported_at
+                }
+
+                /// Get the time between
+                #[doc = concat!("[", stringify!($ErrorName), "::init] being called and the error being reported.")]
+                ///
+                /// Unlike [reported_at](Self::reported_at), this is not affected by changes to the
+                /// system clock. It is zero for errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init].")]
+                pub fn elapsed_since_init(&self) -> Duration {
+                    STARTED
+                        .get()
+                        .map(|started| self.reported.saturating_duration_since(*started))
+                        .unwrap_or_default()
+                }
+
+                /// Get how severe the error is.
+                pub fn severity(&self) -> Severity {
+                    self.severity
+                }
+
+                /// Get the name of the thread which reported the error, if it was named.
+                pub fn thread_name(&self) -> Option<&str> {
+                    self.thread_name.as_deref()
+                }
+
+                /// Get the id of the thread which reported the error.
+                pub fn thread_id(&self) -> ThreadId {
+                    self.thread_id
+                }
+
+                /// Get the location in the source code where the error was reported.
+                pub fn location(&self) -> &'static Location<'static> {
+                    self.location
+                }
+
+                /// Get the backtrace of the thread which reported the error, if one was captured.
+                ///
+                /// Backtraces are only captured when the `backtrace` feature is enabled, and
+                /// [Backtrace::capture](std::backtrace::Backtrace::capture) is enabled by the
+                /// `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment variables.
+                pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+                    self.backtrace.as_ref()
+                }
+
+                /// Get the key of the error this error was reported as a child of, if any.
+                ///
+                /// The parent may since have been removed, in which case the key no longer refers
+                /// to an error.
+                pub fn parent(&self) -> Option<DefaultKey> {
+                    self.parent
+                }
+
+                /// Get the errors returned by
+                #[doc = concat!("[", stringify!($Thread), "::done] in the order they were reported.")]
+                ///
+                /// Errors are ordered by when they were reported on their own thread, so errors
+                /// reported at nearly the same time on different threads may be stored in a
+                /// different order.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// let first = report!("first");
+                /// let second = report!("second");
+                /// let errors = et.done();
+                /// let keys = DocTest::sorted_by_time(&errors).into_iter().map(|(key, _)| key);
+                /// assert_eq!(vec![first, second], keys.collect::<Vec<_>>());
+                /// ```
+                pub fn sorted_by_time(errors: &SlotMap<DefaultKey, $ErrorName>) -> Vec<(DefaultKey, &$ErrorName)> {
+                    let mut sorted = errors.iter().collect::<Vec<_>>();
+                    sorted.sort_by_key(|&(key, error)| (error.reported, key));
+                    sorted
+                }
+
+                /// Get the errors returned by
+                #[doc = concat!("[", stringify!($Thread), "::done] from most to least severe.")]
+                ///
+                /// Errors of the same severity are in the order they were reported, as with
+                #[doc = concat!("[", stringify!($ErrorName), "::sorted_by_time].")]
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// use error_report::Severity;
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// error_report::warn_report!(DocTest, "minor");
+                /// report!("major");
+                /// let errors = et.done();
+                /// let sorted = DocTest::sorted_by_severity(&errors);
+                /// assert_eq!(Severity::Error, sorted[0].1.severity());
+                /// ```
+                pub fn sorted_by_severity(errors: &SlotMap<DefaultKey, $ErrorName>) -> Vec<(DefaultKey, &$ErrorName)> {
+                    let mut sorted = Self::sorted_by_time(errors);
+                    // stable, so errors of the same severity stay in the order they were reported
+                    sorted.sort_by_key(|(_, error)| std::cmp::Reverse(error.severity));
+                    sorted
+                }
+
+                /// Initialize the error collector thread.
+                ///
+                #[doc = concat!("This is done as a non-associated function on [", stringify!($Thread), "] to require the")]
+                #[doc = concat!("user to not discard the [", stringify!($Thread), "] prematurely. This is important as its")]
+                /// [Drop] implementation quits the error collector thread, dropping the [Receiver] and
+                /// thus causing any subsequent error reports to panic.
+                ///
+                /// # Panics
+                ///
+                /// The function must not already have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// ```
+                pub fn init(error_thread: &mut $Thread) {
+                    let (message_tx, message_rx) = flume::unbounded();
+
+                    // hold the lock until MSG_TX is set, so no reports are buffered after this
+                    let mut pending = PENDING.lock().unwrap();
+                    MSG_TX.set(message_tx).expect(INIT_MSG);
+                    STARTED.get_or_init(Instant::now);
+                    let pending = std::mem::take(&mut *pending);
+
+                    let handle = std::thread::spawn(|| handle_messages(message_rx, pending));
+
+                    error_thread.handle = Some(handle);
+                }
+
+                /// Report an error.
+                ///
+                #[doc = concat!("See also [", stringify!($report), "!].")]
+                ///
+                /// If
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                #[doc = concat!("has not been called yet, the error is buffered until it is and a [null](slotmap::Key::null)")]
+                /// key is returned. At most [PRE_INIT_CAPACITY] errors
+                /// are buffered, and any further errors are discarded.
+                ///
+                /// On the error collector thread, such as from
+                #[doc = concat!("[", stringify!($ErrorName), "::for_each] or middleware, the error is queued without waiting for its key, and a")]
+                /// [null](slotmap::Key::null) key is returned.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report(error: Error) -> DefaultKey {
+                    send_error(new_error(error, None))
+                }
+
+                /// Report an error along with extra information.
+                ///
+                /// Unlike reporting and then updating the error, no other function of this reporter
+                /// can observe the error without its extra information.
+                ///
+                /// Errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                #[doc = concat!("is called are buffered, as with [", stringify!($ErrorName), "::report].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report_with_extra(error: Error, extra: $T) -> DefaultKey {
+                    send_error(new_error(error, Some(extra)))
+                }
 
-            loop {
-                let message = message_rx.recv();
-                match message {
-                    Ok(Message::Error(error, sender)) => {
-                        let key = errors.insert($ErrorName { error, extra: None });
-                        sender.send(key).expect(INIT_MSG);
+                /// Report an error which is more or less severe than usual.
+                ///
+                #[doc = concat!("Errors reported any other way are [Severity::Error]. Errors reported before [", stringify!($ErrorName), "::init]")]
+                #[doc = concat!("is called are buffered, as with [", stringify!($ErrorName), "::report].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report_with_severity(error: Error, severity: Severity) -> DefaultKey {
+                    send_error($ErrorName { severity, ..new_error(error, None) })
+                }
+
+                /// Report an error which was caused by, or is part of, another error.
+                ///
+                /// Errors reported this way form a tree which can be walked with
+                #[doc = concat!("[", stringify!($ErrorName), "::children], or with [", stringify!($ErrorName), "::parent] once [", stringify!($Thread), "::done] has been")]
+                /// called. Removing the parent does not remove its children.
+                ///
+                /// Errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                #[doc = concat!("is called are buffered, as with [", stringify!($ErrorName), "::report].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report_child(parent: DefaultKey, error: Error) -> DefaultKey {
+                    send_error($ErrorName { parent: Some(parent), ..new_error(error, None) })
+                }
+
+                /// Report a child of another error along with extra information.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::report_child] and [", stringify!($ErrorName), "::report_with_extra].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report_child_with_extra(parent: DefaultKey, error: Error, extra: $T) -> DefaultKey {
+                    send_error($ErrorName { parent: Some(parent), ..new_error(error, Some(extra)) })
+                }
+
+                /// Report several errors at once.
+                ///
+                /// The errors are sent to the error collector thread together, which is cheaper than
+                /// reporting them one at a time. The keys are returned in the same order as the errors.
+                /// Errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                #[doc = concat!("is called are buffered, as with [", stringify!($ErrorName), "::report].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                #[track_caller]
+                pub fn report_batch(errors: impl IntoIterator<Item = Error>) -> Vec<DefaultKey> {
+                    // closures can't be #[track_caller]
+                    let location = Location::caller();
+                    let errors = errors
+                        .into_iter()
+                        .map(|error| $ErrorName { location, ..new_error(error, None) });
+                    if MSG_TX.get().is_none() || on_collector_thread() {
+                        return errors.map(send_error).collect();
                     }
 
-                    Ok(Message::Update(key, extra)) => {
-                        if let Some(error) = errors.get_mut(key) {
-                            error.extra = Some(extra);
+                    let errors = errors.collect();
+                    expect_reply(request(|keys_tx| Message::Batch(errors, keys_tx)))
+                }
+
+                /// Report every `Err` in `results`, and collect the `Ok` values.
+                ///
+                #[doc = concat!("The errors are reported together with [", stringify!($ErrorName), "::report_batch].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($Thread), "::done] must not have been called.")]
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// let numbers = DocTest::report_all(["1", "two", "3"].iter().map(|s| s.parse::<i32>()));
+                /// assert_eq!(vec![1, 3], numbers);
+                /// assert_eq!(1, et.done().len());
+                /// ```
+                #[track_caller]
+                pub fn report_all<T, E: Into<Error>>(
+                    results: impl IntoIterator<Item = Result<T, E>>,
+                ) -> Vec<T> {
+                    let mut oks = Vec::new();
+                    let mut errors = Vec::new();
+                    for result in results {
+                        match result {
+                            Ok(value) => oks.push(value),
+                            Err(error) => errors.push(error.into()),
                         }
                     }
 
-                    Ok(Message::ForEach(mut f)) => {
-                        for (_, error) in errors.iter() {
-                            f(error);
-                        }
+                    if !errors.is_empty() {
+                        Self::report_batch(errors);
                     }
+                    oks
+                }
 
-                    Ok(Message::ForEachMut(mut f)) => {
-                        for (_, error) in errors.iter_mut() {
-                            f(error);
-                        }
+                /// Create a guard which reports an error if it is dropped before it is disarmed.
+                ///
+                /// See [EarlyExitGuard].
+                #[track_caller]
+                pub fn on_early_exit(message: impl Into<String>) -> EarlyExitGuard<Self> {
+                    EarlyExitGuard::new(message)
+                }
+
+                /// Open a named partition of this reporter.
+                ///
+                /// See [Partition].
+                pub fn partition(name: impl Into<String>) -> Partition<Self> {
+                    Partition::new(name)
+                }
+
+                /// Report an error as part of a partition.
+                ///
+                /// The error is stored as usual, and is also removed and returned when the partition is
+                #[doc = concat!("drained with [", stringify!($ErrorName), "::drain_partition]. Errors buffered or discarded")]
+                /// while collection is paused do not become part of the partition.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                #[track_caller]
+                pub fn report_to_partition(partition: &str, error: Error) -> DefaultKey {
+                    let error = new_error(error, None);
+                    expect_reply(request(|key_tx| Message::ReportToPartition(partition.to_string(), error, key_tx)))
+                }
+
+                /// Remove every error reported as part of a partition, and return them along with
+                /// their keys in the order they were reported.
+                ///
+                /// The partition may be reported to again afterward, and other partitions are not
+                /// affected.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn drain_partition(partition: &str) -> Vec<(DefaultKey, $ErrorName)> {
+                    expect_reply(request(|errors_tx| Message::DrainPartition(partition.to_string(), errors_tx)))
+                }
+
+                /// Keep only the errors reported within `window`, or every error if it is `None`.
+                ///
+                /// Older errors are evicted before the error collector thread processes each message,
+                /// and whenever it finishes, so they are never observed by other functions of this
+                /// reporter. Ages are measured with [Instant], so changes to the system clock do not
+                /// affect eviction.
+                /// Evicted errors are passed to the function set by
+                #[doc = concat!("[", stringify!($ErrorName), "::set_archive], if any.")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// use std::time::Duration;
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// DocTest::set_retention(Some(Duration::from_millis(250)));
+                /// report!("old");
+                /// std::thread::sleep(Duration::from_millis(300));
+                /// report!("recent");
+                /// assert_eq!(1, et.done().len());
+                /// ```
+                pub fn set_retention(window: Option<Duration>) {
+                    send(Message::SetRetention(window));
+                }
+
+                /// Set a function to receive each error evicted by
+                #[doc = concat!("[", stringify!($ErrorName), "::set_retention], along with its key, replacing any")]
+                /// previous function.
+                ///
+                /// The function is executed on the error collector thread, so it must not call any
+                /// other functions of this reporter.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn set_archive(f: impl FnMut(DefaultKey, $ErrorName) + Send + 'static) {
+                    send(Message::SetArchive(Box::new(f)));
+                }
+
+                /// Update an error with additional information, replacing any it already had.
+                ///
+                /// Returns whether the key referred to an error. If it did not, such as because the
+                /// error was removed, nothing is updated.
+                ///
+                /// On the error collector thread, such as in
+                #[doc = concat!("[", stringify!($ErrorName), "::for_each], middleware, or an archive function, waiting would deadlock, so the")]
+                #[doc = concat!("update is queued as with [", stringify!($ErrorName), "::update_detached], and false is returned since it")]
+                /// is not yet known whether the key refers to an error.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                #[doc = concat!("have been called. See [", stringify!($ErrorName), "::try_update] for a non-panicking version.")]
+                pub fn update(key: DefaultKey, extra: $T) -> bool {
+                    if on_collector_thread() {
+                        send(Message::Update(key, extra, None));
+                        return false;
+                    }
+                    expect_reply(Self::try_update(key, extra))
+                }
+
+                /// Update an error with additional information, without waiting to find out whether
+                /// the key referred to an error.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::update].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn update_detached(key: DefaultKey, extra: $T) {
+                    send(Message::Update(key, extra, None));
+                }
+
+                /// Add additional information to an error, keeping any it already had.
+                ///
+                /// Extra information appended from any thread is kept in the order the error
+                /// collector thread receives it, and
+                #[doc = concat!("[", stringify!($ErrorName), "::extra] returns the most recent.")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn append_extra(key: DefaultKey, extra: $T) {
+                    send(Message::AppendExtra(key, extra));
+                }
+
+                /// Modify the most recent extra information of an error in place.
+                ///
+                /// The function is given `None` if the error has no extra information yet, and may
+                /// set it to initialize it, or set it to `None` to remove it. It is executed on the
+                /// error collector thread, so it must not call any other functions of this reporter.
+                /// If the key no longer refers to an error, the function is not called.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn update_with(key: DefaultKey, f: impl FnOnce(&mut Option<$T>) + Send + 'static) {
+                    send(Message::UpdateWith(key, Box::new(f)));
+                }
+
+                /// Report an error, or fail if the error collector thread is not running.
+                ///
+                #[doc = concat!("Unlike [", stringify!($ErrorName), "::report], errors are not buffered before [", stringify!($ErrorName), "::init]")]
+                /// is called, so this is suitable for library code which cannot be sure that the
+                /// reporter was initialized.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// use error_report::ReportError;
+                /// error_report::make_reporter!(DocTest);
+                /// assert_eq!(Err(ReportError::NotInitialized), report!(try: "too early"));
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// assert!(report!(try: "just right").is_ok());
+                /// et.done();
+                /// assert_eq!(Err(ReportError::ShutDown), report!(try: "too late"));
+                /// ```
+                #[track_caller]
+                pub fn try_report(error: Error) -> Result<DefaultKey, ReportError> {
+                    try_send_error(new_error(error, None))
+                }
+
+                /// Report an error which is more or less severe than usual, or fail if the error
+                /// collector thread is not running.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::try_report] and [", stringify!($ErrorName), "::report_with_severity].")]
+                #[track_caller]
+                pub fn try_report_with_severity(error: Error, severity: Severity) -> Result<DefaultKey, ReportError> {
+                    try_send_error($ErrorName { severity, ..new_error(error, None) })
+                }
+
+                /// Like
+                #[doc = concat!("[", stringify!($ErrorName), "::try_report_with_severity],")]
+                /// but record `location` as where the error was reported instead of the caller.
+                ///
+                /// Useful for reporting on behalf of code which ran earlier, such as from a guard's
+                /// [Drop] implementation.
+                pub fn try_report_at(error: Error, severity: Severity, location: &'static Location<'static>) -> Result<DefaultKey, ReportError> {
+                    try_send_error($ErrorName { severity, location, ..new_error(error, None) })
+                }
+
+                /// Update an error with additional information, and return whether the key referred to
+                /// an error, or fail if the error collector thread is not running.
+                ///
+                #[doc = concat!("Unlike [", stringify!($ErrorName), "::update], this fails with [ReportError::Reentrant] on the error collector")]
+                /// thread.
+                pub fn try_update(key: DefaultKey, extra: $T) -> Result<bool, ReportError> {
+                    request(|found_tx| Message::Update(key, extra, Some(found_tx)))
+                }
+
+                /// Update an error, or report a new one if it is no longer stored.
+                ///
+                /// If `key` refers to a stored error, its extra information is replaced with `extra`
+                /// and `error` is discarded. Otherwise, `error` is reported along with `extra`. Both
+                /// happen atomically on the error collector thread. Pass a [null](slotmap::Key::null) key to always
+                /// report a new error.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// use slotmap::{DefaultKey, Key};
+                /// error_report::make_reporter!(DocTest<u32>);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// let mut key = DefaultKey::null();
+                /// for attempt in 1..=3 {
+                ///     let error = anyhow::anyhow!("attempt {attempt} failed");
+                ///     key = DocTest::report_or_update(key, error, attempt);
+                /// }
+                /// let errors = et.done();
+                /// assert_eq!(1, errors.len());
+                /// assert_eq!(Some(&3), errors[key].extra());
+                /// ```
+                #[track_caller]
+                pub fn report_or_update(key: DefaultKey, error: Error, extra: $T) -> DefaultKey {
+                    let error = new_error(error, Some(extra));
+                    expect_reply(request(|key_tx| Message::ReportOrUpdate(key, error, key_tx)))
+                }
+
+                /// Record an attempt at the operation which caused an error.
+                ///
+                /// Any previous outcome is cleared, since another attempt is being made.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn record_attempt(
+                    key: DefaultKey,
+                    attempt: u32,
+                    max_attempts: Option<u32>,
+                    next_retry: Option<std::time::SystemTime>,
+                ) {
+                    send(Message::Retry(
+                        key,
+                        Box::new(move |retry| {
+                            retry.attempt = attempt;
+                            retry.max_attempts = max_attempts;
+                            retry.next_retry = next_retry;
+                            retry.outcome = None;
+                        }),
+                    ));
+                }
+
+                /// Record the final outcome of the operation which caused an error.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn record_outcome(key: DefaultKey, outcome: $crate::RetryOutcome) {
+                    send(Message::Retry(
+                        key,
+                        Box::new(move |retry| {
+                            retry.next_retry = None;
+                            retry.outcome = Some(outcome);
+                        }),
+                    ));
+                }
+
+                /// Execute a function for each error.
+                ///
+                /// This does not wait for the function to finish. It is executed on the error collector
+                /// thread once every message sent before it has been processed, so it may capture
+                /// state such as a counter or a [Sender] to send results back, but it must not call
+                /// any other functions of this reporter.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn for_each(f: impl FnMut(&$ErrorName) + Send + 'static) {
+                    send(Message::ForEach(Box::new(f)));
+                }
+
+                /// Execute a function for each error, mutably.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::for_each].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn for_each_mut(f: impl FnMut(&mut $ErrorName) + Send + 'static) {
+                    send(Message::ForEachMut(Box::new(f)));
+                }
+
+                /// Keep only the errors for which a function returns true, and remove the rest.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::for_each] and [", stringify!($ErrorName), "::remove].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn retain(mut f: impl FnMut(&$ErrorName) -> bool + Send + 'static) {
+                    Self::retain_with_key(move |_, error| f(error));
+                }
+
+                /// Keep only the errors for which a function returns true, and remove the rest. The
+                /// function is also given the key of each error.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::retain].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn retain_with_key(f: impl FnMut(DefaultKey, &$ErrorName) -> bool + Send + 'static) {
+                    send(Message::Retain(Box::new(f)));
+                }
+
+                /// Combine every error into a single value, and wait for the result.
+                ///
+                /// The fold is executed on the error collector thread, so it sees every error reported
+                /// before this call, but `f` must not call any other functions of this reporter.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest<String>);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// report!("dang");
+                /// let key = report!("heck");
+                /// DocTest::update(key, String::from("more info"));
+                /// let with_extra = DocTest::fold(0, |n, error| n + error.extra().is_some() as usize);
+                /// assert_eq!(1, with_extra);
+                /// ```
+                pub fn fold<Acc: Send + 'static>(
+                    init: Acc,
+                    mut f: impl FnMut(Acc, &$ErrorName) -> Acc + Send + 'static,
+                ) -> Acc {
+                    Self::with_errors(move |errors| errors.values().fold(init, |acc, error| f(acc, error)))
+                }
+
+                /// Execute a function with every error, and return its result.
+                ///
+                /// The function is executed on the error collector thread once every message sent
+                /// before it has been processed, so it may only call the functions of this reporter
+                /// which do not wait for a reply. Errors reported from it are queued, and their keys
+                /// are [null](slotmap::Key::null). Functions which wait for a reply, such as this one,
+                /// panic with [REENTRANT_MSG] instead of deadlocking, which stops the error collector
+                /// thread.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// let key = report!("dang");
+                /// report!("heck");
+                /// let matching = DocTest::with_errors(|errors| {
+                ///     errors.iter().filter(|(_, error)| error.to_string() == "dang").count()
+                /// });
+                /// assert_eq!(1, matching);
+                /// assert!(DocTest::with_errors(move |errors| errors.contains_key(key)));
+                /// ```
+                pub fn with_errors<R: Send + 'static>(
+                    f: impl FnOnce(&SlotMap<DefaultKey, $ErrorName>) -> R + Send + 'static,
+                ) -> R {
+                    expect_reply(request(|reply_tx| {
+                        Message::Fold(Box::new(move |errors| {
+                            let _ = reply_tx.send(f(errors));
+                        }))
+                    }))
+                }
+
+                /// Execute a function with every error, mutably, and return its result.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::with_errors]. Errors inserted by the function are not evicted by")]
+                #[doc = concat!("[", stringify!($ErrorName), "::set_retention].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn with_errors_mut<R: Send + 'static>(
+                    f: impl FnOnce(&mut SlotMap<DefaultKey, $ErrorName>) -> R + Send + 'static,
+                ) -> R {
+                    expect_reply(request(|reply_tx| {
+                        Message::FoldMut(Box::new(move |errors| {
+                            let _ = reply_tx.send(f(errors));
+                        }))
+                    }))
+                }
+
+                /// Register a function to be called when the number of queued messages crosses a
+                /// watermark.
+                ///
+                /// `callback` is called with [Watermark::High] and the queue depth once the depth
+                /// reaches `high`, and then with [Watermark::Low] once it falls back to `low`, before
+                /// it may be called with [Watermark::High] again. It is called from the thread which
+                /// crossed the watermark, which is the error collector thread for [Watermark::Low], so
+                /// it must not call any other functions of this reporter.
+                ///
+                /// # Panics
+                ///
+                /// The function must not already have been called, and `low` must not be greater than
+                /// `high`.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// DocTest::set_watermarks(10_000, 100, |watermark, queued| {
+                ///     eprintln!("error queue is {watermark:?}: {queued} messages");
+                /// });
+                /// ```
+                pub fn set_watermarks(
+                    high: usize,
+                    low: usize,
+                    callback: impl Fn($crate::Watermark, usize) + Send + Sync + 'static,
+                ) {
+                    assert!(low <= high, "low watermark must not be greater than high watermark");
+                    let watermarks = Watermarks {
+                        high,
+                        low,
+                        callback: Box::new(callback),
+                    };
+                    if WATERMARKS.set(watermarks).is_err() {
+                        panic!("set_watermarks() should be called once");
                     }
+                }
 
-                    Ok(Message::Quit) => {
-                        break;
+                /// Get the progress of the error collector thread.
+                ///
+                /// This does not wait for the error collector thread, so it may be used to monitor how
+                /// far behind it is.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called.")]
+                pub fn progress() -> $crate::Progress {
+                    let msg_tx = MSG_TX.get().expect(INIT_MSG);
+                    $crate::Progress {
+                        processed: PROCESSED.load(Ordering::Relaxed),
+                        queued: msg_tx.len(),
                     }
+                }
 
-                    Err(RecvError::Disconnected) => {
-                        break;
+                /// Get a snapshot of the health of the error collector thread.
+                ///
+                /// This does not wait for the error collector thread, so it may be used for health
+                /// checks even when it is far behind or no longer running.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called.")]
+                pub fn status() -> $crate::CollectorStatus {
+                    let started = STARTED.get().expect(INIT_MSG);
+                    let last_processed = match LAST_PROCESSED.load(Ordering::Relaxed) {
+                        0 => None,
+                        nanos => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos)),
+                    };
+                    $crate::CollectorStatus {
+                        state: *STATE.lock().unwrap_or_else(PoisonError::into_inner),
+                        uptime: started.elapsed(),
+                        last_processed,
                     }
                 }
-            }
 
-            errors
+                /// Add a function to the end of the middleware chain.
+                ///
+                /// Each newly reported error is passed through the middleware chain in the order the
+                /// functions were added, before it is stored. A function may transform the error, or
+                /// return `None` to filter it out, in which case the remaining functions are skipped and
+                /// the reporter receives a [null](slotmap::Key::null) key. Errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                /// was called do not pass through the chain.
+                ///
+                /// Middleware is executed on the error collector thread, so it must not call any other
+                /// functions of this reporter.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest<String>);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// // drop noisy errors
+                /// DocTest::add_middleware(|error| {
+                ///     (!error.error().to_string().contains("noisy")).then_some(error)
+                /// });
+                /// // then tag the rest
+                /// DocTest::add_middleware(|mut error| {
+                ///     if let Some(extra) = error.extra_mut() {
+                ///         extra.insert_str(0, "tagged: ");
+                ///     }
+                ///     Some(error)
+                /// });
+                /// report!("noisy");
+                /// let key = report!("important", extra: String::from("details"));
+                /// let errors = et.done();
+                /// assert_eq!(1, errors.len());
+                /// assert_eq!("tagged: details", errors[key].extra().unwrap());
+                /// ```
+                pub fn add_middleware(
+                    f: impl FnMut($ErrorName) -> Option<$ErrorName> + Send + 'static,
+                ) {
+                    send(Message::AddMiddleware(Box::new(f)));
+                }
+
+                /// Forward each newly reported error to the parent reporter `P`, as well as storing
+                /// it.
+                ///
+                /// The forwarded error is rendered as a message with `label` as its context, so the
+                /// parent can tell where it came from. Its severity is kept, but extra information is
+                /// not forwarded. Forwarding is implemented as middleware, so it sees errors as
+                /// transformed by the middleware added before it.
+                ///
+                /// Errors which cannot be forwarded, such as because the parent has finished, are
+                /// still stored by this reporter. `P` must not be this reporter, or forward errors
+                /// back to it.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(App, thread = AppThread, macro = report_app);
+                /// error_report::make_reporter!(Library, thread = LibraryThread, macro = report_library);
+                /// let mut app = AppThread::default();
+                /// App::init(&mut app);
+                /// let mut library = LibraryThread::default();
+                /// Library::init(&mut library);
+                /// Library::forward_to::<App>("library");
+                /// report_library!("dang");
+                /// assert_eq!(1, library.done().len());
+                /// let app = app.done();
+                /// assert_eq!("library: dang", app.values().next().unwrap().to_string());
+                /// ```
+                pub fn forward_to<P: $crate::Reporter>(label: impl Into<String>) {
+                    let label = label.into();
+                    Self::add_middleware(move |error| {
+                        // P may have finished already, which must not stop this reporter
+                        let _ = P::try_report_with_severity(
+                            anyhow::anyhow!("{:#}", error.error).context(label.clone()),
+                            error.severity,
+                        );
+                        Some(error)
+                    });
+                }
+
+                /// Pause collection until
+                #[doc = concat!("[", stringify!($ErrorName), "::resume]")]
+                /// is called.
+                ///
+                /// Errors reported while collection is paused are handled according to `policy`, and
+                /// are given a [null](slotmap::Key::null) key. Pausing again changes the policy.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn pause(policy: $crate::PausePolicy) {
+                    send(Message::Pause(policy));
+                }
+
+                /// Resume collection, storing any errors buffered while it was paused.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn resume() {
+                    send(Message::Resume);
+                }
+
+                /// Drop errors less severe than `severity` instead of storing them, starting with the
+                /// errors reported after this call.
+                ///
+                #[doc = concat!("Dropped errors are counted by [", stringify!($Thread), "::dropped], and are not passed to middleware.")]
+                /// The minimum may be changed at any time, and is [Severity::Info] by default, so that
+                /// every error is stored.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// use error_report::Severity;
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// DocTest::set_min_severity(Severity::Error);
+                /// error_report::warn_report!(DocTest, "noisy");
+                /// report!("important");
+                /// assert_eq!(1, DocTest::count());
+                /// assert_eq!(1, et.dropped());
+                /// ```
+                pub fn set_min_severity(severity: Severity) {
+                    send(Message::SetMinSeverity(severity));
+                }
+
+                /// Get the number of errors discarded while collection was paused with
+                /// [PausePolicy::Discard].
+                ///
+                /// This does not wait for the error collector thread.
+                pub fn discarded() -> usize {
+                    DISCARDED.load(Ordering::Relaxed)
+                }
+
+                /// Get the number of errors.
+                ///
+                /// Every error reported before this call, on any thread, whose key has been returned
+                /// is counted.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn count() -> usize {
+                    Self::with_errors(|errors| errors.len())
+                }
+
+                /// Check whether there are no errors.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::count].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn is_empty() -> bool {
+                    Self::count() == 0
+                }
+
+                /// Get the number of errors of each severity.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::count].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn count_by_severity() -> SeverityCounts {
+                    Self::with_errors(|errors| errors.values().map(|error| error.severity).collect())
+                }
+
+                /// Get the number of errors reported by each thread, from the thread which reported
+                /// the most to the thread which reported the fewest.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::count].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// report!("dang");
+                /// std::thread::spawn(|| report!("heck")).join().unwrap();
+                /// report!("darn");
+                /// let counts = DocTest::count_by_thread();
+                /// assert_eq!(std::thread::current().id(), counts[0].id);
+                /// assert_eq!([2, 1], [counts[0].count, counts[1].count]);
+                /// ```
+                pub fn count_by_thread() -> Vec<$crate::ThreadCount> {
+                    Self::with_errors(|errors| {
+                        let mut counts = HashMap::<ThreadId, $crate::ThreadCount>::new();
+                        for error in errors.values() {
+                            counts
+                                .entry(error.thread_id)
+                                .or_insert_with(|| $crate::ThreadCount {
+                                    id: error.thread_id,
+                                    name: error.thread_name.clone(),
+                                    count: 0,
+                                })
+                                .count += 1;
+                        }
+                        let mut counts = counts.into_values().collect::<Vec<_>>();
+                        counts.sort_by(|a, b| b.count.cmp(&a.count));
+                        counts
+                    })
+                }
+
+                /// Get the number of errors reported from each location in the source code, from the
+                /// location which reported the most to the location which reported the fewest.
+                ///
+                /// Locations with the same number of errors are ordered by file, line, and column.
+                #[doc = concat!("See [", stringify!($ErrorName), "::count] and [", stringify!($ErrorName), "::location].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                ///
+                /// # Examples
+                ///
+                /// ```
+                /// error_report::make_reporter!(DocTest);
+                /// let mut et = ErrorThread::default();
+                /// DocTest::init(&mut et);
+                /// for i in 0..3 {
+                ///     report!("attempt {i} failed");
+                /// }
+                /// report!("gave up");
+                /// let counts = DocTest::count_by_location();
+                /// assert_eq!([3, 1], [counts[0].count, counts[1].count]);
+                /// ```
+                pub fn count_by_location() -> Vec<$crate::LocationCount> {
+                    Self::with_errors(|errors| {
+                        let mut counts = HashMap::<&'static Location<'static>, usize>::new();
+                        for error in errors.values() {
+                            *counts.entry(error.location).or_default() += 1;
+                        }
+                        let mut counts = counts
+                            .into_iter()
+                            .map(|(location, count)| $crate::LocationCount { location, count })
+                            .collect::<Vec<_>>();
+                        counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.location.cmp(b.location)));
+                        counts
+                    })
+                }
+
+                /// Get the keys of every error, without their contents.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn keys() -> Vec<DefaultKey> {
+                    Self::with_errors(|errors| errors.keys().collect())
+                }
+
+                /// Check whether a key still refers to an error.
+                ///
+                /// Keys are generational, so this is false for the null key and for keys of errors
+                /// which have since been removed, even if their slot has been reused.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn contains(key: DefaultKey) -> bool {
+                    Self::with_errors(move |errors| errors.contains_key(key))
+                }
+
+                /// Get the keys of the errors reported as children of an error.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::report_child].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn children(key: DefaultKey) -> Vec<DefaultKey> {
+                    Self::with_errors(move |errors| {
+                        errors
+                            .iter()
+                            .filter(|(_, error)| error.parent == Some(key))
+                            .map(|(child, _)| child)
+                            .collect()
+                    })
+                }
+
+                /// Get an owned copy of an error, if the key still refers to one.
+                ///
+                /// The [Error] itself cannot be cloned, so it is rendered on the error collector
+                /// thread instead. This is only available when the extra type implements [Clone].
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn get(key: DefaultKey) -> Option<ErrorSnapshot<$T>>
+                where
+                    // the binder keeps this from being rejected outright when the bound doesn't hold
+                    for<'a> $T: Clone,
+                {
+                    Self::with_errors(move |errors| {
+                        errors.get(key).map(|error| ErrorSnapshot {
+                            message: format!("{:#}", error.error),
+                            chain: format!("{:?}", error.error),
+                            extra: error.extras.last().cloned(),
+                            reported_at: error.reported_at,
+                            severity: error.severity,
+                        })
+                    })
+                }
+
+                /// Remove an error, such as one which turned out to be spurious.
+                ///
+                /// Returns whether the key referred to an error. Afterward it no longer does, so
+                /// updating it does nothing. Children of the error are kept, and become orphans.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn remove(key: DefaultKey) -> bool {
+                    expect_reply(request(|removed_tx| Message::Remove(key, removed_tx)))
+                }
+
+                /// Remove every error, to start over without stopping the error collector thread.
+                ///
+                #[doc = concat!("See [", stringify!($ErrorName), "::drain].")]
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn clear() {
+                    Self::drain();
+                }
+
+                /// Remove and return every error, to start over without stopping the error collector
+                /// thread.
+                ///
+                /// Errors reported on any thread are removed if the error collector thread received
+                /// them before this call, and kept otherwise. Every key handed out for a removed error
+                /// no longer refers to one, even once its slot is reused, so updating it does nothing.
+                #[doc = concat!("[", stringify!($Thread), "::done] returns only the errors stored afterward.")]
+                /// Errors buffered while collection is paused are not stored yet, so they are kept.
+                ///
+                /// # Panics
+                ///
+                #[doc = concat!("[", stringify!($ErrorName), "::init] must have been called and [", stringify!($Thread), "::done] must not")]
+                /// have been called.
+                pub fn drain() -> Vec<(DefaultKey, $ErrorName)> {
+                    expect_reply(request(Message::Drain))
+                }
+            }
+
+            impl $crate::Reporter for $ErrorName {
+                #[track_caller]
+                fn report(error: Error) -> DefaultKey {
+                    $ErrorName::report(error)
+                }
+
+                #[track_caller]
+                fn report_with_severity(error: Error, severity: Severity) -> DefaultKey {
+                    $ErrorName::report_with_severity(error, severity)
+                }
+
+                #[track_caller]
+                fn try_report_with_severity(error: Error, severity: Severity) -> Result<DefaultKey, ReportError> {
+                    $ErrorName::try_report_with_severity(error, severity)
+                }
+
+                fn try_report_at(error: Error, severity: Severity, location: &'static Location<'static>) -> Result<DefaultKey, ReportError> {
+                    $ErrorName::try_report_at(error, severity, location)
+                }
+
+                #[track_caller]
+                fn report_to_partition(partition: &str, error: Error) -> DefaultKey {
+                    $ErrorName::report_to_partition(partition, error)
+                }
+
+                fn drain_partition(partition: &str) -> Vec<(DefaultKey, Self)> {
+                    $ErrorName::drain_partition(partition)
+                }
+            }
+
+            impl std::fmt::Display for $ErrorName {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{:#}", self.error)?;
+                    if let Some(extra) = self.extra() {
+                        write!(f, " ({extra:?})")?;
+                    }
+                    Ok(())
+                }
+            }
+
+            /// There is no [source](std::error::Error::source), because [Display](std::fmt::Display)
+            /// already renders the whole chain, and reporters which walk the chain would print every
+            /// cause twice. The causes are available through
+            #[doc = concat!("[", stringify!($ErrorName), "::error].")]
+            impl std::error::Error for $ErrorName {}
+
+            /// Message types that the library may send to the error collector thread.
+            enum Message {
+                /// An error that is reported, possibly along with extra information.
+                ///
+                /// Requires a sender to be send along with it so that the error reporting thread may
+                /// reply with the slotmap's key.
+                Error($ErrorName, Sender<DefaultKey>),
+
+                /// Several errors that are reported together.
+                Batch(Vec<$ErrorName>, Sender<Vec<DefaultKey>>),
+
+                /// An error that is reported as part of a partition.
+                ReportToPartition(String, $ErrorName, Sender<DefaultKey>),
+
+                /// Remove and send back the errors of a partition.
+                DrainPartition(String, Sender<Vec<(DefaultKey, $ErrorName)>>),
+
+                /// Remove an error, and reply whether there was one.
+                Remove(DefaultKey, Sender<bool>),
+
+                /// Remove and send back every error.
+                Drain(Sender<Vec<(DefaultKey, $ErrorName)>>),
+
+                /// Update an error, and reply whether there was one if asked.
+                Update(DefaultKey, $T, Option<Sender<bool>>),
+
+                /// Add extra information to an error.
+                AppendExtra(DefaultKey, $T),
+
+                /// Modify the most recent extra information of an error.
+                UpdateWith(DefaultKey, Box<dyn FnOnce(&mut Option<$T>) + Send>),
+
+                /// Update the retry information of an error.
+                Retry(DefaultKey, Box<dyn FnOnce(&mut $crate::Retry) + Send>),
+
+                /// Update an error, or report a new one if the key is not stored.
+                ///
+                /// Replies with the key of the updated or newly reported error.
+                ReportOrUpdate(DefaultKey, $ErrorName, Sender<DefaultKey>),
+
+                /// Execute a function for each error.
+                ForEach(Box<dyn FnMut(&$ErrorName) + Send>),
+
+                /// Execute a function for each error, mutably.
+                ForEachMut(Box<dyn FnMut(&mut $ErrorName) + Send>),
+
+                /// Keep only the errors for which the function returns true.
+                Retain(Box<dyn FnMut(DefaultKey, &$ErrorName) -> bool + Send>),
+
+                /// Fold over every error.
+                ///
+                /// The accumulator type is erased by the function, which sends the result back itself.
+                Fold(Box<dyn FnOnce(&SlotMap<DefaultKey, $ErrorName>) + Send>),
+
+                /// Execute a function with every error, mutably.
+                ///
+                /// The result type is erased by the function, which sends the result back itself.
+                FoldMut(Box<dyn FnOnce(&mut SlotMap<DefaultKey, $ErrorName>) + Send>),
+
+                /// Add a function to the end of the middleware chain.
+                AddMiddleware(Middleware),
+
+                /// Pause collection.
+                Pause(PausePolicy),
+
+                /// Resume collection, storing any buffered errors.
+                Resume,
+
+                /// Keep only the errors reported within a window.
+                SetRetention(Option<Duration>),
+
+                /// Drop errors less severe than a minimum.
+                SetMinSeverity(Severity),
+
+                /// Set the function which receives evicted errors.
+                SetArchive(Archive),
+
+                /// Exit the error collector thread.
+                ///
+                /// This is necessary because we hold onto a static [Sender], so the channel will never
+                /// be closed under normal circumstances.
+                Quit,
+            }
+
+            impl std::fmt::Debug for Message {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        Message::Error(error, _) => write!(f, "Error({error:?})"),
+                        Message::Batch(errors, _) => write!(f, "Batch({errors:?})"),
+                        Message::ReportToPartition(partition, err, _) => {
+                            write!(f, "ReportToPartition({partition:?}, {err:?})")
+                        }
+                        Message::DrainPartition(partition, _) => {
+                            write!(f, "DrainPartition({partition:?})")
+                        }
+                        Message::Remove(_, _) => write!(f, "Remove"),
+                        Message::Drain(_) => write!(f, "Drain"),
+                        Message::Update(_, s, _) => write!(f, "Update({s:?})"),
+                        Message::AppendExtra(_, s) => write!(f, "AppendExtra({s:?})"),
+                        Message::UpdateWith(_, _) => write!(f, "UpdateWith(...)"),
+                        Message::Retry(_, _) => write!(f, "Retry(...)"),
+                        Message::ReportOrUpdate(_, err, _) => write!(f, "ReportOrUpdate({err:?})"),
+                        Message::ForEach(_) => write!(f, "ForEach(...)"),
+                        Message::ForEachMut(_) => write!(f, "ForEachMut(...)"),
+                        Message::Retain(_) => write!(f, "Retain(...)"),
+                        Message::Fold(_) => write!(f, "Fold(...)"),
+                        Message::FoldMut(_) => write!(f, "FoldMut(...)"),
+                        Message::AddMiddleware(_) => write!(f, "AddMiddleware(...)"),
+                        Message::Pause(policy) => write!(f, "Pause({policy:?})"),
+                        Message::Resume => write!(f, "Resume"),
+                        Message::SetRetention(window) => write!(f, "SetRetention({window:?})"),
+                        Message::SetMinSeverity(severity) => write!(f, "SetMinSeverity({severity:?})"),
+                        Message::SetArchive(_) => write!(f, "SetArchive(...)"),
+                        Message::Quit => write!(f, "Quit"),
+                    }
+                }
+            }
+
+            impl $Thread {
+                /// Get the final list of errors.
+                ///
+                /// There should be no more calls to library functions after this call.
+                ///
+                /// # Panics
+                ///
+                /// Panics if
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                /// has not been called.
+                pub fn done(mut self) -> SlotMap<DefaultKey, $ErrorName> {
+                    let tx = MSG_TX.get().expect(INIT_MSG);
+                    tx.send(Message::Quit).expect(INIT_MSG);
+                    self.handle.take().expect(INIT_MSG).join().unwrap()
+                }
+
+                /// Get the number of messages the error collector thread has finished processing.
+                ///
+                /// This does not wait for the error collector thread.
+                pub fn processed(&self) -> usize {
+                    PROCESSED.load(Ordering::Relaxed)
+                }
+
+                /// Get the number of errors currently stored by the error collector thread.
+                ///
+                /// This does not wait for the error collector thread.
+                pub fn stored(&self) -> usize {
+                    STORED.load(Ordering::Relaxed)
+                }
+
+                /// Get the number of errors which were dropped instead of stored, because the
+                /// buffer for errors reported before
+                #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                /// was full, they were less severe than the minimum set by
+                #[doc = concat!("[", stringify!($ErrorName), "::set_min_severity], they were filtered out by middleware, or they were")]
+                /// discarded while collection was paused.
+                ///
+                /// This does not wait for the error collector thread.
+                pub fn dropped(&self) -> usize {
+                    DROPPED.load(Ordering::Relaxed)
+                }
+
+                /// Get the number of errors which were evicted because they were older than the
+                /// retention window set by
+                #[doc = concat!("[", stringify!($ErrorName), "::set_retention].")]
+                ///
+                /// This does not wait for the error collector thread.
+                pub fn evicted(&self) -> usize {
+                    EVICTED.load(Ordering::Relaxed)
+                }
+            }
+
+            $crate::__cfg_async! {
+                impl $Thread {
+                    /// Get the final list of errors, without blocking the current thread.
+                    ///
+                    #[doc = concat!("Like [", stringify!($Thread), "::done], but the error collector thread is joined on a")]
+                    /// separate thread, and the returned future completes once it has finished
+                    /// processing every message.
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics if
+                    #[doc = concat!("[", stringify!($ErrorName), "::init]")]
+                    /// has not been called.
+                    pub async fn join_async(mut self) -> SlotMap<DefaultKey, $ErrorName> {
+                        let tx = MSG_TX.get().expect(INIT_MSG);
+                        tx.send(Message::Quit).expect(INIT_MSG);
+
+                        let handle = self.handle.take().expect(INIT_MSG);
+                        let (errors_tx, errors_rx) = flume::bounded(1);
+                        std::thread::spawn(move || {
+                            let _ = errors_tx.send(handle.join());
+                        });
+                        errors_rx.recv_async().await.expect(INIT_MSG).unwrap()
+                    }
+                }
+            }
+
+            impl Drop for $Thread {
+                fn drop(&mut self) {
+                    let tx = MSG_TX.get().expect(INIT_MSG);
+                    let _x = tx.send(Message::Quit);
+                }
+            }
+
+            /// Send a message to the error collector thread.
+            fn send(message: Message) {
+                try_send(message).expect(INIT_MSG);
+            }
+
+            /// Send a message to the error collector thread, if it is running.
+            fn try_send(message: Message) -> Result<(), ReportError> {
+                let msg_tx = MSG_TX.get().ok_or(ReportError::NotInitialized)?;
+                msg_tx.send(message).map_err(|_| ReportError::ShutDown)?;
+
+                if let Some(watermarks) = WATERMARKS.get() {
+                    let queued = msg_tx.len();
+                    if queued >= watermarks.high && !ABOVE_HIGH.swap(true, Ordering::Relaxed) {
+                        $crate::__instrument!(debug!(queued, "queue depth rose to high watermark"));
+                        (watermarks.callback)(Watermark::High, queued);
+                    }
+                }
+                Ok(())
+            }
+
+            /// Whether this is the error collector thread.
+            fn on_collector_thread() -> bool {
+                COLLECTOR_THREAD.get() == Some(&std::thread::current().id())
+            }
+
+            /// Panic with the reason the error collector thread could not reply to a request.
+            fn expect_reply<R>(reply: Result<R, ReportError>) -> R {
+                match reply {
+                    Ok(reply) => reply,
+                    Err(ReportError::Reentrant) => panic!("{REENTRANT_MSG}"),
+                    Err(error) => panic!("{INIT_MSG}: {error:?}"),
+                }
+            }
+
+            /// Send an error to the error collector thread, or buffer it if it has not been started.
+            fn send_error(error: $ErrorName) -> DefaultKey {
+                if MSG_TX.get().is_none() {
+                    let mut pending = PENDING.lock().unwrap();
+                    // init may have been called while we were waiting for the lock
+                    if MSG_TX.get().is_none() {
+                        if pending.len() < PRE_INIT_CAPACITY {
+                            pending.push(error);
+                        } else {
+                            DROPPED.fetch_add(1, Ordering::Relaxed);
+                        }
+                        return DefaultKey::null();
+                    }
+                }
+
+                // the error collector thread would wait for itself forever, so queue the error
+                if on_collector_thread() {
+                    let (key_tx, _) = flume::bounded(1);
+                    send(Message::Error(error, key_tx));
+                    return DefaultKey::null();
+                }
+
+                try_send_error(error).expect(INIT_MSG)
+            }
+
+            /// Send an error to the error collector thread, if it is running.
+            fn try_send_error(error: $ErrorName) -> Result<DefaultKey, ReportError> {
+                request(|key_tx| Message::Error(error, key_tx))
+            }
+
+            /// Send a message to the error collector thread, and wait for its reply.
+            fn request<R>(message: impl FnOnce(Sender<R>) -> Message) -> Result<R, ReportError> {
+                // the error collector thread would wait for itself forever
+                if on_collector_thread() {
+                    return Err(ReportError::Reentrant);
+                }
+
+                let (reply_tx, reply_rx) = flume::bounded(1);
+                try_send(message(reply_tx))?;
+                recv_reply(reply_rx)
+            }
+
+            /// Wait for the error collector thread to reply to a message.
+            ///
+            /// If the error collector thread shuts down before processing the message, the message
+            /// stays in the channel, since [MSG_TX] keeps it open. So instead of waiting forever, give
+            /// up once the error collector thread has stopped receiving.
+            fn recv_reply<R>(reply_rx: Receiver<R>) -> Result<R, ReportError> {
+                let msg_tx = MSG_TX.get().ok_or(ReportError::NotInitialized)?;
+                loop {
+                    match reply_rx.recv_timeout(Duration::from_millis(10)) {
+                        Ok(reply) => return Ok(reply),
+                        Err(RecvTimeoutError::Disconnected) => return Err(ReportError::ShutDown),
+                        Err(RecvTimeoutError::Timeout) if msg_tx.is_disconnected() => {
+                            // the reply may have been sent just before the thread stopped
+                            return reply_rx.try_recv().map_err(|_| ReportError::ShutDown);
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                    }
+                }
+            }
+
+            /// Set the state reported by
+            #[doc = concat!("[", stringify!($ErrorName), "::status].")]
+            fn set_state(state: CollectorState) {
+                *STATE.lock().unwrap_or_else(PoisonError::into_inner) = state;
+            }
+
+            /// Create an error on the thread reporting it.
+            #[track_caller]
+            fn new_error(error: Error, extra: Option<$T>) -> $ErrorName {
+                let thread = std::thread::current();
+                $ErrorName {
+                    error,
+                    extras: extra.into_iter().collect(),
+                    retry: None,
+                    reported_at: SystemTime::now(),
+                    reported: Instant::now(),
+                    severity: Severity::Error,
+                    thread_name: thread.name().map(String::from),
+                    thread_id: thread.id(),
+                    location: Location::caller(),
+                    backtrace: $crate::__capture_backtrace(),
+                    parent: None,
+                }
+            }
+
+            /// A function which receives errors evicted because they are older than the retention
+            /// window.
+            type Archive = Box<dyn FnMut(DefaultKey, $ErrorName) + Send>;
+
+            /// A function applied to each newly reported error before it is stored.
+            type Middleware = Box<dyn FnMut($ErrorName) -> Option<$ErrorName> + Send>;
+
+            /// The state of the error collector thread.
+            struct Collector {
+                errors: SlotMap<DefaultKey, $ErrorName>,
+                paused: Option<PausePolicy>,
+                buffered: Vec<$ErrorName>,
+                middleware: Vec<Middleware>,
+                min_severity: Severity,
+                partitions: HashMap<String, Vec<DefaultKey>>,
+                /// The partition of each stored error which was reported as part of one.
+                partition_of: HashMap<DefaultKey, String>,
+                retention: Option<Duration>,
+                archive: Option<Archive>,
+                /// The keys of the stored errors ordered by when they were reported, maintained only
+                /// while there is a retention window.
+                by_age: BTreeSet<(Instant, DefaultKey)>,
+            }
+
+            impl Collector {
+                /// Store a reported error, unless it is filtered out or collection is paused.
+                fn store(&mut self, error: $ErrorName) -> DefaultKey {
+                    if error.severity < self.min_severity {
+                        $crate::__instrument!(trace!(severity = ?error.severity, "error below minimum severity"));
+                        DROPPED.fetch_add(1, Ordering::Relaxed);
+                        return DefaultKey::null();
+                    }
+
+                    let Some(error) = self.middleware.iter_mut().try_fold(error, |error, f| f(error))
+                    else {
+                        $crate::__instrument!(trace!("error filtered out by middleware"));
+                        DROPPED.fetch_add(1, Ordering::Relaxed);
+                        return DefaultKey::null();
+                    };
+
+                    match self.paused {
+                        None => {
+                            let key = self.insert(error);
+                            $crate::__instrument!(trace!(?key, "error stored"));
+                            key
+                        }
+
+                        Some(PausePolicy::Buffer) => {
+                            self.buffered.push(error);
+                            DefaultKey::null()
+                        }
+
+                        Some(PausePolicy::Discard) => {
+                            DISCARDED.fetch_add(1, Ordering::Relaxed);
+                            DROPPED.fetch_add(1, Ordering::Relaxed);
+                            DefaultKey::null()
+                        }
+                    }
+                }
+
+                /// Store an error.
+                fn insert(&mut self, error: $ErrorName) -> DefaultKey {
+                    let reported = error.reported;
+                    let key = self.errors.insert(error);
+                    if self.retention.is_some() {
+                        self.by_age.insert((reported, key));
+                    }
+                    key
+                }
+
+                /// Remove a stored error.
+                fn remove(&mut self, key: DefaultKey) -> Option<$ErrorName> {
+                    let error = self.errors.remove(key)?;
+                    self.by_age.remove(&(error.reported, key));
+                    if let Some(partition) = self.partition_of.remove(&key) {
+                        if let Some(keys) = self.partitions.get_mut(&partition) {
+                            keys.retain(|&other| other != key);
+                            if keys.is_empty() {
+                                self.partitions.remove(&partition);
+                            }
+                        }
+                    }
+                    Some(error)
+                }
+
+                /// Keep only the errors reported within a window.
+                fn set_retention(&mut self, window: Option<Duration>) {
+                    self.retention = window;
+                    self.by_age = match window {
+                        Some(_) => self.errors.iter().map(|(key, error)| (error.reported, key)).collect(),
+                        None => BTreeSet::new(),
+                    };
+                }
+
+                /// Evict the errors which are older than the retention window.
+                fn evict(&mut self) {
+                    let Some(cutoff) = self.retention.and_then(|window| Instant::now().checked_sub(window))
+                    else {
+                        return;
+                    };
+
+                    while let Some(&(reported, key)) = self.by_age.first() {
+                        if reported >= cutoff {
+                            break;
+                        }
+                        self.by_age.pop_first();
+                        if let Some(error) = self.remove(key) {
+                            EVICTED.fetch_add(1, Ordering::Relaxed);
+                            if let Some(archive) = &mut self.archive {
+                                archive(key, error);
+                            }
+                        }
+                    }
+                }
+            }
+
+            fn handle_messages(
+                message_rx: Receiver<Message>,
+                pending: Vec<$ErrorName>,
+            ) -> SlotMap<DefaultKey, $ErrorName> {
+                /// Marks the error collector thread as dead when it exits, even if it panics.
+                struct Exit;
+
+                impl Drop for Exit {
+                    fn drop(&mut self) {
+                        set_state(CollectorState::Dead);
+                    }
+                }
+
+                let _exit = Exit;
+                let _ = COLLECTOR_THREAD.set(std::thread::current().id());
+                $crate::__instrument!(let _span = debug_span!("collector", reporter = stringify!($ErrorName)));
+
+                let mut collector = Collector {
+                    errors: SlotMap::new(),
+                    paused: None,
+                    buffered: Vec::new(),
+                    middleware: Vec::new(),
+                    min_severity: Severity::Info,
+                    partitions: HashMap::new(),
+                    partition_of: HashMap::new(),
+                    retention: None,
+                    archive: None,
+                    by_age: BTreeSet::new(),
+                };
+
+                for error in pending {
+                    collector.insert(error);
+                }
+                STORED.store(collector.errors.len(), Ordering::Relaxed);
+
+                loop {
+                    let message = message_rx.recv();
+                    $crate::__instrument!(let _span = trace_span!("message", queued = message_rx.len()));
+                    $crate::__instrument!(trace!(received = ?message, "message received"));
+                    // before the message is handled, so that it never observes expired errors
+                    collector.evict();
+                    match message {
+                        Ok(Message::Error(error, sender)) => {
+                            let key = collector.store(error);
+                            // nobody waits for errors reported from this thread
+                            let _ = sender.send(key);
+                        }
+
+                        Ok(Message::Batch(errors, sender)) => {
+                            let keys = errors
+                                .into_iter()
+                                .map(|error| collector.store(error))
+                                .collect();
+                            sender.send(keys).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::ReportToPartition(partition, error, sender)) => {
+                            let key = collector.store(error);
+                            if !key.is_null() {
+                                collector.partitions.entry(partition.clone()).or_default().push(key);
+                                collector.partition_of.insert(key, partition);
+                            }
+                            sender.send(key).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::DrainPartition(partition, sender)) => {
+                            let keys = collector.partitions.remove(&partition).unwrap_or_default();
+                            let errors = keys
+                                .into_iter()
+                                .filter_map(|key| Some((key, collector.remove(key)?)))
+                                .collect();
+                            sender.send(errors).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::Remove(key, sender)) => {
+                            sender.send(collector.remove(key).is_some()).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::Drain(sender)) => {
+                            collector.partitions.clear();
+                            collector.partition_of.clear();
+                            collector.by_age.clear();
+                            sender.send(collector.errors.drain().collect()).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::Update(key, extra, sender)) => {
+                            let error = collector.errors.get_mut(key);
+                            let found = error.is_some();
+                            if let Some(error) = error {
+                                error.extras = vec![extra];
+                            }
+                            if let Some(sender) = sender {
+                                sender.send(found).expect(INIT_MSG);
+                            }
+                        }
+
+                        Ok(Message::AppendExtra(key, extra)) => {
+                            if let Some(error) = collector.errors.get_mut(key) {
+                                error.extras.push(extra);
+                            }
+                        }
+
+                        Ok(Message::UpdateWith(key, f)) => {
+                            if let Some(error) = collector.errors.get_mut(key) {
+                                let mut extra = error.extras.pop();
+                                f(&mut extra);
+                                error.extras.extend(extra);
+                            }
+                        }
+
+                        Ok(Message::Retry(key, f)) => {
+                            if let Some(error) = collector.errors.get_mut(key) {
+                                f(error.retry.get_or_insert_with(Default::default));
+                            }
+                        }
+
+                        Ok(Message::ReportOrUpdate(key, error, sender)) => {
+                            let key = match collector.errors.get_mut(key) {
+                                Some(existing) => {
+                                    existing.extras = error.extras;
+                                    key
+                                }
+                                None => collector.store(error),
+                            };
+                            sender.send(key).expect(INIT_MSG);
+                        }
+
+                        Ok(Message::ForEach(mut f)) => {
+                            for (_, error) in collector.errors.iter() {
+                                f(error);
+                            }
+                        }
+
+                        Ok(Message::ForEachMut(mut f)) => {
+                            for (_, error) in collector.errors.iter_mut() {
+                                f(error);
+                            }
+                        }
+
+                        Ok(Message::Retain(mut f)) => {
+                            let removed = collector
+                                .errors
+                                .iter()
+                                .filter(|&(key, error)| !f(key, error))
+                                .map(|(key, _)| key)
+                                .collect::<Vec<_>>();
+                            for key in removed {
+                                collector.remove(key);
+                            }
+                        }
+
+                        Ok(Message::Fold(f)) => {
+                            f(&collector.errors);
+                        }
+
+                        Ok(Message::FoldMut(f)) => {
+                            f(&mut collector.errors);
+                        }
+
+                        Ok(Message::AddMiddleware(f)) => {
+                            collector.middleware.push(f);
+                        }
+
+                        Ok(Message::Pause(policy)) => {
+                            collector.paused = Some(policy);
+                            set_state(CollectorState::Paused(policy));
+                        }
+
+                        Ok(Message::Resume) => {
+                            collector.paused = None;
+                            set_state(CollectorState::Running);
+                            for error in std::mem::take(&mut collector.buffered) {
+                                collector.insert(error);
+                            }
+                        }
+
+                        Ok(Message::SetRetention(window)) => {
+                            collector.set_retention(window);
+                        }
+
+                        Ok(Message::SetMinSeverity(severity)) => {
+                            collector.min_severity = severity;
+                        }
+
+                        Ok(Message::SetArchive(f)) => {
+                            collector.archive = Some(f);
+                        }
+
+                        Ok(Message::Quit) => {
+                            break;
+                        }
+
+                        Err(RecvError::Disconnected) => {
+                            break;
+                        }
+                    }
+
+                    if let Ok(since_epoch) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+                        LAST_PROCESSED.store(since_epoch.as_nanos() as u64, Ordering::Relaxed);
+                    }
+                    STORED.store(collector.errors.len(), Ordering::Relaxed);
+                    PROCESSED.fetch_add(1, Ordering::Relaxed);
+
+                    if let Some(watermarks) = WATERMARKS.get() {
+                        let queued = message_rx.len();
+                        if queued <= watermarks.low && ABOVE_HIGH.swap(false, Ordering::Relaxed) {
+                            $crate::__instrument!(debug!(queued, "queue depth fell to low watermark"));
+                            (watermarks.callback)(Watermark::Low, queued);
+                        }
+                    }
+                }
+
+                set_state(CollectorState::Draining);
+                $crate::__instrument!(let _span = debug_span!("drain", buffered = collector.buffered.len()));
+                for error in std::mem::take(&mut collector.buffered) {
+                    collector.insert(error);
+                }
+                collector.evict();
+                STORED.store(collector.errors.len(), Ordering::Relaxed);
+                $crate::__instrument!(debug!(errors = collector.errors.len(), "error collector thread finished"));
+
+                collector.errors
+            }
+        };
+    };
+
+    (@options $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, $Key:tt, $derive:tt] thread = $NewThread:ident $(, $($rest:tt)*)?) => {
+        $crate::make_reporter!(@options $ErrorName<$T> [$NewThread, $report, $Key, $derive] $($($rest)*)?);
+    };
+
+    (@options $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, $Key:tt, $derive:tt] macro = $new_report:ident $(, $($rest:tt)*)?) => {
+        $crate::make_reporter!(@options $ErrorName<$T> [$Thread, $new_report, $Key, $derive] $($($rest)*)?);
+    };
+
+    (@options $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, $Key:tt, $derive:tt] key = $NewKey:ident $(, $($rest:tt)*)?) => {
+        $crate::make_reporter!(@options $ErrorName<$T> [$Thread, $report, [$NewKey], $derive] $($($rest)*)?);
+    };
+
+    (@options $ErrorName:ident < $T:ty > [$Thread:ident, $report:ident, $Key:tt, $derive:tt] derive($($new_derive:ident),* $(,)?) $(, $($rest:tt)*)?) => {
+        $crate::make_reporter!(@options $ErrorName<$T> [$Thread, $report, $Key, [$($new_derive)*]] $($($rest)*)?);
+    };
+
+    ($ErrorName:ident < $T:ty > $(, $($options:tt)*)?) => {
+        $crate::make_reporter!(@options $ErrorName<$T> [ErrorThread, report, [], []] $($($options)*)?);
+    };
+
+    ($ErrorName:ident $(, $($options:tt)*)?) => {
+        $crate::make_reporter!($ErrorName<()> $(, $($options)*)?);
+    };
+}
+
+/// The message which appears when the library is misused.
+pub const INIT_MSG: &str = "init() should be called once, and its result not discarded.\nlet errors = error_report::init(); // do not assign to _, you must include a name";
+
+/// The message which appears when a function which waits for the error collector thread is called
+/// from the error collector thread itself.
+pub const REENTRANT_MSG: &str = "this function waits for the error collector thread, so it cannot be called from that thread, such as from for_each or middleware";
+
+/// The maximum number of errors buffered by a reporter before it is initialized.
+pub const PRE_INIT_CAPACITY: usize = 64;
+
+/// The retry lifecycle of the operation which caused an error.
+///
+/// See [example::ExampleReporter::record_attempt] and [example::ExampleReporter::record_outcome].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Retry {
+    /// The number of the most recent attempt.
+    pub attempt: u32,
+
+    /// The number of attempts which will be made before giving up, if limited.
+    pub max_attempts: Option<u32>,
+
+    /// When the next attempt will be made, if one is scheduled.
+    pub next_retry: Option<std::time::SystemTime>,
+
+    /// The final outcome, once no more attempts will be made.
+    pub outcome: Option<RetryOutcome>,
+}
+
+/// The final outcome of a retried operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOutcome {
+    /// A later attempt succeeded.
+    Succeeded,
+
+    /// The operation was abandoned.
+    GaveUp,
+}
+
+/// Why an error could not be sent to an error collector thread.
+///
+/// See [example::ExampleReporter::try_report].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportError {
+    /// The reporter has not been initialized.
+    NotInitialized,
+
+    /// The error collector thread has shut down.
+    ShutDown,
+
+    /// The function was called from the error collector thread, such as from middleware, where
+    /// waiting for a reply would deadlock.
+    Reentrant,
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::NotInitialized => f.write_str("the reporter has not been initialized"),
+            ReportError::ShutDown => f.write_str("the error collector thread has shut down"),
+            ReportError::Reentrant => {
+                f.write_str("the reporter was called from its own error collector thread")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+/// What to do with errors reported while collection is paused.
+///
+/// See [example::ExampleReporter::pause].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PausePolicy {
+    /// Keep the errors, and store them when collection is resumed or the error collector thread
+    /// finishes.
+    Buffer,
+
+    /// Count the errors, and otherwise discard them.
+    Discard,
+}
+
+/// A queue depth threshold crossed by the messages waiting for an error collector thread.
+///
+/// See [example::ExampleReporter::set_watermarks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watermark {
+    /// The queue depth has risen to the high watermark.
+    High,
+
+    /// The queue depth has fallen back to the low watermark.
+    Low,
+}
+
+/// A snapshot of the progress of an error collector thread.
+///
+/// See [example::ExampleReporter::progress].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of messages the error collector thread has finished processing.
+    pub processed: usize,
+
+    /// The number of messages waiting to be processed.
+    pub queued: usize,
+}
+
+impl Progress {
+    /// Get the number of messages the error collector thread has received, including those
+    /// waiting to be processed.
+    pub fn received(&self) -> usize {
+        self.processed + self.queued
+    }
+}
+
+/// What an error collector thread is currently doing.
+///
+/// See [example::ExampleReporter::status].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectorState {
+    /// Errors are being stored as they are reported.
+    Running,
+
+    /// Collection is paused, and errors are handled according to the policy.
+    Paused(PausePolicy),
+
+    /// The error collector thread has been told to finish, and is storing any buffered errors.
+    Draining,
+
+    /// The error collector thread has finished, or has panicked.
+    Dead,
+}
+
+/// A snapshot of the health of an error collector thread.
+///
+/// See [example::ExampleReporter::status].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectorStatus {
+    /// What the error collector thread is currently doing.
+    pub state: CollectorState,
+
+    /// The time since the error collector thread was started.
+    pub uptime: std::time::Duration,
+
+    /// When the error collector thread last finished processing a message, if it has processed
+    /// any.
+    pub last_processed: Option<std::time::SystemTime>,
+}
+
+/// An owned copy of a single error.
+///
+/// See [example::ExampleReporter::get].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorSnapshot<T> {
+    /// The error and its chain of causes, rendered like `{:#}`.
+    pub message: String,
+
+    /// The error and its chain of causes, rendered like `{:?}`, including the backtrace if one
+    /// was captured.
+    pub chain: String,
+
+    /// The extra information, if any.
+    pub extra: Option<T>,
+
+    /// When the error was reported.
+    pub reported_at: std::time::SystemTime,
+
+    /// How severe the error is.
+    pub severity: Severity,
+}
+
+/// How severe a reported error is.
+///
+/// See [example::ExampleReporter::report_with_severity].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A note which does not indicate a problem on its own.
+    Info,
+
+    /// A problem which did not stop the operation.
+    Warning,
+
+    /// A problem which stopped the operation.
+    #[default]
+    Error,
+
+    /// A problem which the program cannot recover from.
+    Fatal,
+}
+
+/// The number of errors of each [Severity].
+///
+/// See [example::ExampleReporter::count_by_severity].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeverityCounts {
+    /// The number of [Severity::Info] errors.
+    pub info: usize,
+
+    /// The number of [Severity::Warning] errors.
+    pub warning: usize,
+
+    /// The number of [Severity::Error] errors.
+    pub error: usize,
+
+    /// The number of [Severity::Fatal] errors.
+    pub fatal: usize,
+}
+
+impl SeverityCounts {
+    /// Get the number of errors of a severity.
+    pub fn get(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Error => self.error,
+            Severity::Fatal => self.fatal,
+        }
+    }
+
+    /// Get the number of errors of every severity.
+    pub fn total(&self) -> usize {
+        self.info + self.warning + self.error + self.fatal
+    }
+}
+
+impl FromIterator<Severity> for SeverityCounts {
+    fn from_iter<I: IntoIterator<Item = Severity>>(iter: I) -> Self {
+        let mut counts = SeverityCounts::default();
+        for severity in iter {
+            match severity {
+                Severity::Info => counts.info += 1,
+                Severity::Warning => counts.warning += 1,
+                Severity::Error => counts.error += 1,
+                Severity::Fatal => counts.fatal += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// The number of errors reported by one thread.
+///
+/// See [example::ExampleReporter::count_by_thread].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadCount {
+    /// The id of the thread.
+    pub id: std::thread::ThreadId,
+
+    /// The name of the thread, if it was named.
+    pub name: Option<String>,
+
+    /// The number of errors the thread reported.
+    pub count: usize,
+}
+
+/// The number of errors reported from one location in the source code.
+///
+/// See [example::ExampleReporter::count_by_location].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocationCount {
+    /// Where the errors were reported.
+    pub location: &'static std::panic::Location<'static>,
+
+    /// The number of errors reported there.
+    pub count: usize,
+}
+
+/// Functionality shared by every error type generated by [make_reporter!].
+///
+/// See [example::ExampleReporter] for the corresponding inherent functions.
+pub trait Reporter {
+    /// Report an error.
+    fn report(error: anyhow::Error) -> DefaultKey;
+
+    /// Report an error which is more or less severe than usual.
+    fn report_with_severity(error: anyhow::Error, severity: Severity) -> DefaultKey;
+
+    /// Report an error which is more or less severe than usual, or fail if the error collector
+    /// thread is not running.
+    fn try_report_with_severity(
+        error: anyhow::Error,
+        severity: Severity,
+    ) -> Result<DefaultKey, ReportError>;
+
+    /// Like [Reporter::try_report_with_severity], but record `location` as where the error was
+    /// reported.
+    fn try_report_at(
+        error: anyhow::Error,
+        severity: Severity,
+        location: &'static std::panic::Location<'static>,
+    ) -> Result<DefaultKey, ReportError>;
+
+    /// Report an error as part of a partition.
+    fn report_to_partition(partition: &str, error: anyhow::Error) -> DefaultKey;
+
+    /// Remove and return every error reported as part of a partition.
+    fn drain_partition(partition: &str) -> Vec<(DefaultKey, Self)>
+    where
+        Self: Sized;
+}
+
+/// Functionality of a reporter which involves its extra information.
+///
+/// Since this trait names the extra type, it is only implemented when requested with
+/// `derive(ReporterExtra)`, which requires the extra type to be public. See [make_reporter!].
+pub trait ReporterExtra: Reporter {
+    /// The type of extra information attached to errors.
+    type Extra: Send + 'static;
+
+    /// Report an error along with extra information.
+    fn report_with_extra(error: anyhow::Error, extra: Self::Extra) -> DefaultKey;
+
+    /// Update an error with additional information, and return whether the key referred to an
+    /// error.
+    fn update(key: DefaultKey, extra: Self::Extra) -> bool;
+}
+
+/// An object-safe handle to a reporter, for code which cannot name the concrete error type.
+///
+/// Libraries can accept a `&dyn ErrorReporter` instead of depending on the application's
+/// reporter. See [handle] for getting one from a reporter, [registry] for looking one up by name,
+/// and [NoopReporter] for a default which ignores errors.
+///
+/// ```
+/// use error_report::{ErrorReporter, NoopReporter};
+/// error_report::make_reporter!(DocTest, derive(ReporterExtra));
+///
+/// fn library_function(reporter: &dyn ErrorReporter) {
+///     reporter.report(anyhow::anyhow!("dang"));
+/// }
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// library_function(error_report::handle::<DocTest>());
+/// library_function(&NoopReporter);
+/// assert_eq!(1, et.done().len());
+/// ```
+pub trait ErrorReporter: Send + Sync {
+    /// Report an error.
+    fn report(&self, error: anyhow::Error) -> DefaultKey;
+
+    /// Update an error with additional information.
+    ///
+    /// If `extra` is not the reporter's extra type, it is given back.
+    fn update(
+        &self,
+        key: DefaultKey,
+        extra: Box<dyn Any + Send>,
+    ) -> Result<(), Box<dyn Any + Send>>;
+}
+
+/// The [ErrorReporter] for a [Reporter].
+struct Handle<R>(PhantomData<fn() -> R>);
+
+/// Get the [ErrorReporter] for the reporter `R`.
+pub fn handle<R: ReporterExtra + 'static>() -> &'static dyn ErrorReporter {
+    &Handle::<R>(PhantomData)
+}
+
+/// An [ErrorReporter] which ignores every error.
+///
+/// Reports return a [null](slotmap::Key::null) key, and updates succeed without doing anything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReporter;
+
+impl ErrorReporter for NoopReporter {
+    fn report(&self, _: anyhow::Error) -> DefaultKey {
+        DefaultKey::null()
+    }
+
+    fn update(&self, _: DefaultKey, _: Box<dyn Any + Send>) -> Result<(), Box<dyn Any + Send>> {
+        Ok(())
+    }
+}
+
+impl<R: ReporterExtra> ErrorReporter for Handle<R> {
+    fn report(&self, error: anyhow::Error) -> DefaultKey {
+        R::report(error)
+    }
+
+    fn update(
+        &self,
+        key: DefaultKey,
+        extra: Box<dyn Any + Send>,
+    ) -> Result<(), Box<dyn Any + Send>> {
+        R::update(key, *extra.downcast::<R::Extra>()?);
+        Ok(())
+    }
+}
+
+/// A global registry of reporters, looked up by name.
+///
+/// ```
+/// error_report::make_reporter!(Network<u16>, derive(ReporterExtra));
+/// error_report::registry::register::<Network>("network");
+///
+/// let mut et = ErrorThread::default();
+/// Network::init(&mut et);
+///
+/// // elsewhere, without access to the Network type
+/// let reporter = error_report::registry::get("network").unwrap();
+/// let key = reporter.report(anyhow::anyhow!("connection reset"));
+/// reporter.update(key, Box::new(8080_u16)).unwrap();
+/// assert!(reporter.update(key, Box::new("wrong type")).is_err());
+///
+/// assert_eq!(Some(&8080), et.done()[key].extra());
+/// ```
+pub mod registry {
+    use {
+        super::{handle, ErrorReporter, ReporterExtra},
+        std::{collections::BTreeMap, sync::RwLock},
+    };
+
+    static REGISTRY: RwLock<BTreeMap<String, &'static dyn ErrorReporter>> =
+        RwLock::new(BTreeMap::new());
+
+    /// Register a reporter under a name.
+    ///
+    /// Returns false, leaving the registry unchanged, if the name is already registered.
+    pub fn register<R: ReporterExtra + 'static>(name: &str) -> bool {
+        let mut registry = REGISTRY.write().unwrap();
+        if registry.contains_key(name) {
+            return false;
+        }
+        registry.insert(name.to_string(), handle::<R>());
+        true
+    }
+
+    /// Get the reporter registered under a name.
+    pub fn get(name: &str) -> Option<&'static dyn ErrorReporter> {
+        REGISTRY.read().unwrap().get(name).copied()
+    }
+
+    /// Get the names of every registered reporter, in sorted order.
+    pub fn names() -> Vec<String> {
+        REGISTRY.read().unwrap().keys().cloned().collect()
+    }
+}
+
+/// Extension trait for reporting the error of a [Result] in place.
+pub trait ResultExt: Sized {
+    /// If this is an `Err`, report the error with `R` together with the extra information produced
+    /// by `extra`, then return the result unchanged.
+    ///
+    /// Since the original error is returned, the reported error is rendered from its alternate
+    /// [Display](std::fmt::Display) output, which includes the full chain of an [anyhow::Error].
+    ///
+    /// ```
+    /// use error_report::ResultExt;
+    /// error_report::make_reporter!(DocTest<String>, derive(ReporterExtra));
+    ///
+    /// let mut et = ErrorThread::default();
+    /// DocTest::init(&mut et);
+    /// let path = "/this/path/does/not/exist";
+    /// let result = std::fs::read_to_string(path)
+    ///     .with_report_context::<DocTest>(|| format!("while reading {path}"));
+    /// assert!(result.is_err());
+    /// assert_eq!(1, et.done().len());
+    /// ```
+    #[track_caller]
+    fn with_report_context<R: ReporterExtra>(self, extra: impl FnOnce() -> R::Extra) -> Self;
+}
+
+impl<T, E: std::fmt::Display> ResultExt for Result<T, E> {
+    #[track_caller]
+    fn with_report_context<R: ReporterExtra>(self, extra: impl FnOnce() -> R::Extra) -> Self {
+        if let Err(error) = &self {
+            R::report_with_extra(anyhow::anyhow!("{error:#}"), extra());
+        }
+        self
+    }
+}
+
+/// Spawn a task on the current [tokio] runtime, reporting its failure with `R`.
+///
+/// If the task returns an `Err` or panics, the error is reported with the name of the task as
+/// context and the returned handle resolves to `None`.
+///
+/// The future is spawned as its own task, which is awaited by the task the returned handle refers
+/// to. Aborting the returned handle therefore does not abort the future itself.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, or if the task fails and `R` has not been
+/// initialized.
+#[cfg(feature = "tokio")]
+pub fn spawn_reported<R, T, E>(
+    name: &str,
+    future: impl std::future::Future<Output = Result<T, E>> + Send + 'static,
+) -> tokio::task::JoinHandle<Option<T>>
+where
+    R: Reporter + 'static,
+    T: Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    let name = name.to_string();
+    let task = tokio::spawn(future);
+
+    tokio::spawn(async move {
+        match task.await {
+            Ok(Ok(value)) => Some(value),
+
+            Ok(Err(error)) => {
+                report_blocking::<R>(error.into().context(format!("task {name} failed"))).await;
+                None
+            }
+
+            Err(error) if error.is_panic() => {
+                let payload = error.into_panic();
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("Box<dyn Any>");
+                let error = anyhow::anyhow!("task {name} panicked: {message}");
+                report_blocking::<R>(error).await;
+                None
+            }
+
+            Err(_) => None,
+        }
+    })
+}
+
+/// Report an error without blocking a runtime worker while waiting for its key.
+#[cfg(feature = "tokio")]
+async fn report_blocking<R: Reporter + 'static>(error: anyhow::Error) {
+    if let Err(error) = tokio::task::spawn_blocking(move || R::report(error)).await {
+        if error.is_panic() {
+            std::panic::resume_unwind(error.into_panic());
+        }
+    }
+}
+
+/// Extension trait for running a [Command] and reporting its failure.
+pub trait CommandExt {
+    /// Run the command to completion, collecting its output.
+    ///
+    /// If the command could not be spawned or exits unsuccessfully, an error describing the
+    /// program, its quoted arguments, and its exit status is reported with `R`. When the command exits
+    /// unsuccessfully, the last [CAPTURED_OUTPUT_LIMIT] bytes of its captured stdout and stderr
+    /// are included as the cause.
+    ///
+    /// ```
+    /// use error_report::CommandExt;
+    /// error_report::make_reporter!(DocTest);
+    ///
+    /// let mut et = ErrorThread::default();
+    /// DocTest::init(&mut et);
+    /// let result = std::process::Command::new("this-program-does-not-exist")
+    ///     .arg("--help")
+    ///     .output_reported::<DocTest>();
+    /// assert!(result.is_err());
+    /// assert_eq!(1, et.done().len());
+    /// ```
+    #[track_caller]
+    fn output_reported<R: Reporter>(&mut self) -> Result<std::process::Output, Reported<R>> {
+        self.output_reported_with_limit(CAPTURED_OUTPUT_LIMIT)
+    }
+
+    /// Like [CommandExt::output_reported], but include at most the last `limit` bytes of stdout
+    /// and stderr in the reported error.
+    #[track_caller]
+    fn output_reported_with_limit<R: Reporter>(
+        &mut self,
+        limit: usize,
+    ) -> Result<std::process::Output, Reported<R>>;
+}
+
+/// The number of bytes at the end of each of stdout and stderr included in the error reported by
+/// [CommandExt::output_reported].
+pub const CAPTURED_OUTPUT_LIMIT: usize = 4 * 1024;
+
+impl CommandExt for Command {
+    #[track_caller]
+    fn output_reported_with_limit<R: Reporter>(
+        &mut self,
+        limit: usize,
+    ) -> Result<std::process::Output, Reported<R>> {
+        // quote each argument so that arguments containing spaces stay distinguishable
+        let command = std::iter::once(self.get_program())
+            .chain(self.get_args())
+            .map(|arg| format!("{arg:?}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // not map_err, since closures can't be #[track_caller]
+        let output = match self.output() {
+            Ok(output) => output,
+            Err(error) => {
+                let error = anyhow::Error::new(error).context(format!("could not run {command}"));
+                return Err(Reported::new(error));
+            }
+        };
+
+        if output.status.success() {
+            return Ok(output);
+        }
+
+        let context = format!("{command} failed with {}", output.status);
+        let captured = [("stdout", &output.stdout), ("stderr", &output.stderr)]
+            .into_iter()
+            .filter_map(|(name, bytes)| output_tail(name, bytes, limit))
+            .collect::<Vec<_>>();
+        let error = match captured.as_slice() {
+            [] => anyhow::Error::msg(context),
+            captured => anyhow::Error::msg(captured.join("\n")).context(context),
+        };
+
+        Err(Reported::new(error))
+    }
+}
+
+/// Render at most the last `limit` bytes of a captured output stream, noting if it was
+/// truncated, or `None` if it is empty.
+fn output_tail(name: &str, bytes: &[u8], limit: usize) -> Option<String> {
+    let mut start = bytes.len().saturating_sub(limit);
+    // don't start in the middle of a UTF-8 sequence
+    while bytes
+        .get(start)
+        .is_some_and(|byte| byte & 0b1100_0000 == 0b1000_0000)
+    {
+        start += 1;
+    }
+
+    let tail = String::from_utf8_lossy(&bytes[start..]);
+    let tail = tail.trim_end();
+    if tail.is_empty() {
+        None
+    } else if start == 0 {
+        Some(format!("{name}:\n{tail}"))
+    } else {
+        Some(format!(
+            "{name} (truncated to the last {} of {} bytes):\n{tail}",
+            bytes.len() - start,
+            bytes.len()
+        ))
+    }
+}
+
+/// A [Read] adapter which reports any [io::Error] passing through it with `R`.
+///
+/// The error is still returned to the caller. Reported errors include the number of bytes
+/// successfully read before the failure. [io::ErrorKind::Interrupted] and
+/// [io::ErrorKind::WouldBlock] are not reported, since callers are expected to retry them.
+pub struct ReportedReader<R, T> {
+    inner: T,
+    offset: u64,
+    reporter: PhantomData<fn() -> R>,
+}
+
+impl<R, T> ReportedReader<R, T> {
+    /// Wrap a reader.
+    pub fn new(inner: T) -> Self {
+        ReportedReader {
+            inner,
+            offset: 0,
+            reporter: PhantomData,
+        }
+    }
+
+    /// Get the number of bytes read so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: Reporter, T: Read> Read for ReportedReader<R, T> {
+    #[track_caller]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Ok(n) => {
+                self.offset += n as u64;
+                Ok(n)
+            }
+            Err(error) => Err(report_io::<R>(error, "read", self.offset)),
+        }
+    }
+}
+
+/// A [Write] adapter which reports any [io::Error] passing through it with `R`.
+///
+/// The error is still returned to the caller. Reported errors include the number of bytes
+/// successfully written before the failure. [io::ErrorKind::Interrupted] and
+/// [io::ErrorKind::WouldBlock] are not reported, since callers are expected to retry them.
+pub struct ReportedWriter<R, T> {
+    inner: T,
+    offset: u64,
+    reporter: PhantomData<fn() -> R>,
+}
+
+impl<R, T> ReportedWriter<R, T> {
+    /// Wrap a writer.
+    pub fn new(inner: T) -> Self {
+        ReportedWriter {
+            inner,
+            offset: 0,
+            reporter: PhantomData,
+        }
+    }
+
+    /// Get the number of bytes written so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: Reporter, T: Write> Write for ReportedWriter<R, T> {
+    #[track_caller]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.inner.write(buf) {
+            Ok(n) => {
+                self.offset += n as u64;
+                Ok(n)
+            }
+            Err(error) => Err(report_io::<R>(error, "write", self.offset)),
+        }
+    }
+
+    #[track_caller]
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner.flush() {
+            Ok(()) => Ok(()),
+            Err(error) => Err(report_io::<R>(error, "flush", self.offset)),
+        }
+    }
+}
+
+/// Report an [io::Error] from a [ReportedReader] or [ReportedWriter], and give it back.
+#[track_caller]
+fn report_io<R: Reporter>(error: io::Error, operation: &str, offset: u64) -> io::Error {
+    if !matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+    ) {
+        R::report(
+            anyhow::anyhow!("{error}").context(format!("{operation} failed at byte {offset}")),
+        );
+    }
+    error
+}
+
+/// Extra information about the HTTP request during which an error occurred.
+///
+/// Use it as the extra type of a reporter to report errors from web handlers without defining a
+/// separate struct for each service.
+///
+/// ```
+/// use error_report::HttpContext;
+/// error_report::make_reporter!(DocTest<HttpContext>);
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// let key = report!("database timed out", extra: HttpContext::new("GET", "/users").with_status(504));
+/// let errors = et.done();
+/// assert_eq!("GET /users 504", errors[key].extra().unwrap().to_string());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpContext {
+    /// The request method.
+    pub method: String,
+
+    /// The request path.
+    pub path: String,
+
+    /// The response status code, if a response was produced.
+    pub status: Option<u16>,
+
+    /// How long the request took to handle, if known.
+    pub duration: Option<std::time::Duration>,
+
+    /// The identifier of the request, if it has one.
+    pub request_id: Option<String>,
+}
+
+impl HttpContext {
+    /// Create a new context for a request.
+    pub fn new(method: impl Into<String>, path: impl Into<String>) -> Self {
+        HttpContext {
+            method: method.into(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the response status code.
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Set how long the request took to handle.
+    pub fn with_duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Set the identifier of the request.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+/// Renders as `METHOD /path status duration request-id`, leaving out anything unknown.
+impl std::fmt::Display for HttpContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(status) = self.status {
+            write!(f, " {status}")?;
+        }
+        if let Some(duration) = self.duration {
+            write!(f, " {duration:?}")?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, " {request_id}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A guard which reports an error with `R` if the scope it is in exits before the guard is
+/// disarmed, by returning early or by unwinding.
+///
+/// The reported error includes the location where the guard was created.
+///
+/// ```
+/// error_report::make_reporter!(DocTest);
+///
+/// fn step(fail: bool) -> Option<()> {
+///     let guard = DocTest::on_early_exit("step did not complete");
+///     fail.then_some(())?;
+///     guard.disarm();
+///     Some(())
+/// }
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// step(false);
+/// step(true);
+/// assert_eq!(1, et.done().len());
+/// ```
+///
+/// Dropping an armed guard never panics, since that would abort the process if the scope is
+/// already unwinding. If `R` is not running, the error is silently lost.
+#[must_use = "the guard reports an error as soon as it is dropped"]
+pub struct EarlyExitGuard<R: Reporter> {
+    message: String,
+    location: &'static std::panic::Location<'static>,
+    armed: bool,
+    reporter: PhantomData<fn() -> R>,
+}
+
+impl<R: Reporter> EarlyExitGuard<R> {
+    /// Create a guard which reports `message` if it is dropped before it is disarmed.
+    #[track_caller]
+    pub fn new(message: impl Into<String>) -> Self {
+        EarlyExitGuard {
+            message: message.into(),
+            location: std::panic::Location::caller(),
+            armed: true,
+            reporter: PhantomData,
+        }
+    }
+
+    /// Disarm the guard, since the scope completed normally.
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl<R: Reporter> Drop for EarlyExitGuard<R> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        let how = if std::thread::panicking() {
+            "unwound"
+        } else {
+            "exited early"
+        };
+        let message = std::mem::take(&mut self.message);
+        let error = anyhow::anyhow!("scope {how} at {}", self.location).context(message);
+        let _ = R::try_report_at(error, Severity::Error, self.location);
+    }
+}
+
+/// A named group of errors within a reporter, which can be drained independently while the
+/// reporter keeps collecting errors for other partitions.
+///
+/// This is useful when one process handles many independent jobs, each of which wants its own
+/// errors when it finishes.
+///
+/// ```
+/// error_report::make_reporter!(DocTest);
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// let job = DocTest::partition("job-42");
+/// job.report(anyhow::anyhow!("dang"));
+/// report!("unrelated");
+/// assert_eq!(1, job.drain().len());
+/// assert_eq!(1, et.done().len());
+/// ```
+pub struct Partition<R> {
+    name: String,
+    reporter: PhantomData<fn() -> R>,
+}
+
+impl<R: Reporter> Partition<R> {
+    /// Open a partition of `R`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Partition {
+            name: name.into(),
+            reporter: PhantomData,
+        }
+    }
+
+    /// Get the name of the partition.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Report an error as part of the partition.
+    #[track_caller]
+    pub fn report(&self, error: anyhow::Error) -> DefaultKey {
+        R::report_to_partition(&self.name, error)
+    }
+
+    /// Remove and return every error reported as part of the partition, along with their keys.
+    pub fn drain(self) -> Vec<(DefaultKey, R)> {
+        R::drain_partition(&self.name)
+    }
+}
+
+/// An error which has already been reported.
+///
+/// Any [std::error::Error] converts into a `Reported<R>` by reporting it with `R`, so the `?`
+/// operator reports errors as they propagate. The key is carried along so that the error may be
+/// updated later.
+///
+/// ```
+/// error_report::make_reporter!(DocTest<String>, derive(ReporterExtra));
+/// use error_report::Reported;
+///
+/// fn parse(s: &str) -> Result<i32, Reported<DocTest>> {
+///     Ok(s.parse::<i32>()?)
+/// }
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// let reported = parse("dang").unwrap_err();
+/// reported.update(String::from("while parsing"));
+/// assert_eq!(1, et.done().len());
+/// ```
+pub struct Reported<R> {
+    key: DefaultKey,
+    message: String,
+    reporter: PhantomData<fn() -> R>,
+}
+
+impl<R: Reporter> Reported<R> {
+    /// Report an error.
+    ///
+    /// Useful for errors which are not [std::error::Error], such as [anyhow::Error] itself.
+    #[track_caller]
+    pub fn new(error: anyhow::Error) -> Self {
+        let message = format!("{error:#}");
+        Reported {
+            key: R::report(error),
+            message,
+            reporter: PhantomData,
+        }
+    }
+}
+
+impl<R: ReporterExtra> Reported<R> {
+    /// Update the error with additional information, and return whether it is still stored.
+    ///
+    /// See [ReporterExtra::update].
+    pub fn update(&self, extra: R::Extra) -> bool {
+        R::update(self.key, extra)
+    }
+}
+
+impl<R> Reported<R> {
+    /// Get the key of the reported error.
+    pub fn key(&self) -> DefaultKey {
+        self.key
+    }
+}
+
+impl<R, E> From<E> for Reported<R>
+where
+    R: Reporter,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[track_caller]
+    fn from(error: E) -> Self {
+        Reported::new(anyhow::Error::new(error))
+    }
+}
+
+impl<R> std::fmt::Debug for Reported<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Reported")
+            .field("key", &self.key)
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl<R> std::fmt::Display for Reported<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Report an error with the reporter `R`, and return it from the current function as a
+/// [Reported].
+///
+/// Like [anyhow::bail!], the remaining arguments are formatted into the error message. The
+/// function's error type must be convertible from [`Reported<R>`](Reported).
+///
+/// ```
+/// error_report::make_reporter!(DocTest);
+/// use error_report::Reported;
+///
+/// fn check(x: i32) -> Result<i32, Reported<DocTest>> {
+///     if x < 0 {
+///         error_report::bail_report!(DocTest, "negative: {x}");
+///     }
+///     Ok(x)
+/// }
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// assert_eq!("negative: -1", check(-1).unwrap_err().to_string());
+/// assert_eq!(1, et.done().len());
+/// ```
+#[macro_export]
+macro_rules! bail_report {
+    ($R:ty, $($arg:tt)+) => {
+        return ::core::result::Result::Err(
+            $crate::Reported::<$R>::new(anyhow::anyhow!($($arg)+)).into(),
+        )
+    };
+}
+
+/// Report an error with the reporter `R` and return it from the current function if a condition
+/// is false.
+///
+/// Like [anyhow::ensure!], the message defaults to the stringified condition. See [bail_report!].
+///
+/// ```
+/// error_report::make_reporter!(DocTest);
+/// use error_report::Reported;
+///
+/// fn check(x: i32) -> Result<i32, Reported<DocTest>> {
+///     error_report::ensure_report!(DocTest, x >= 0, "invariant violated: {x}");
+///     error_report::ensure_report!(DocTest, x != 0);
+///     Ok(x)
+/// }
+///
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+/// assert_eq!("invariant violated: -1", check(-1).unwrap_err().to_string());
+/// assert_eq!("condition failed: `x != 0`", check(0).unwrap_err().to_string());
+/// assert_eq!(2, et.done().len());
+/// ```
+#[macro_export]
+macro_rules! ensure_report {
+    ($R:ty, $cond:expr $(,)?) => {
+        if !$cond {
+            $crate::bail_report!($R, ::core::concat!("condition failed: `", ::core::stringify!($cond), "`"));
+        }
+    };
+
+    ($R:ty, $cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::bail_report!($R, $($arg)+);
+        }
+    };
+}
+
+/// Report an expression and its value with the reporter `R`, like [dbg!], and return the value.
+///
+/// The reported error includes the location of the invocation, the expression, and the [Debug]
+/// representation of its value. With `on_err:`, the expression must be a [Result], and it is only
+/// reported if it is an `Err`.
+///
+/// ```
+/// error_report::make_reporter!(DocTest);
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+///
+/// let x = error_report::report_dbg!(DocTest, 2 + 2);
+/// assert_eq!(4, x);
+/// let ok = error_report::report_dbg!(DocTest, on_err: "4".parse::<i32>());
+/// let err = error_report::report_dbg!(DocTest, on_err: "four".parse::<i32>());
+/// assert!(ok.is_ok() && err.is_err());
+///
+/// let errors = et.done();
+/// let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+/// messages.sort();
+/// assert!(messages[0].ends_with("\"four\".parse::<i32>() = Err(ParseIntError { kind: InvalidDigit })"));
+/// assert!(messages[1].ends_with("2 + 2 = 4"));
+/// ```
+#[macro_export]
+macro_rules! report_dbg {
+    ($R:ty, on_err: $e:expr $(,)?) => {
+        match $e {
+            result => {
+                if let ::core::result::Result::Err(error) = &result {
+                    <$R as $crate::Reporter>::report(anyhow::anyhow!(
+                        "[{}:{}:{}] {} = Err({:?})",
+                        ::core::file!(),
+                        ::core::line!(),
+                        ::core::column!(),
+                        ::core::stringify!($e),
+                        error,
+                    ));
+                }
+                result
+            }
+        }
+    };
+
+    ($R:ty, $e:expr $(,)?) => {
+        match $e {
+            value => {
+                <$R as $crate::Reporter>::report(anyhow::anyhow!(
+                    "[{}:{}:{}] {} = {:?}",
+                    ::core::file!(),
+                    ::core::line!(),
+                    ::core::column!(),
+                    ::core::stringify!($e),
+                    &value,
+                ));
+                value
+            }
+        }
+    };
+}
+
+/// Report a [Severity::Warning] with the reporter `R`, and return its key.
+///
+/// Like [anyhow::anyhow!], the remaining arguments are a format string with its arguments, or an
+/// error value.
+///
+/// ```
+/// use error_report::Severity;
+/// error_report::make_reporter!(DocTest);
+/// let mut et = ErrorThread::default();
+/// DocTest::init(&mut et);
+///
+/// let path = "config.toml";
+/// let key = error_report::warn_report!(DocTest, "{path} is deprecated");
+/// let errors = et.done();
+/// assert_eq!(Severity::Warning, errors[key].severity());
+/// ```
+#[macro_export]
+macro_rules! warn_report {
+    ($R:ty, $($arg:tt)+) => {
+        <$R as $crate::Reporter>::report_with_severity(
+            anyhow::anyhow!($($arg)+),
+            $crate::Severity::Warning,
+        )
+    };
+}
+
+/// Expand to the given items only when the `async` feature is enabled.
+///
+/// A `#[cfg]` in the output of [make_reporter!] would check the features of the calling crate.
+#[cfg(feature = "async")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_async {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+/// Expand to the given items only when the `async` feature is enabled.
+#[cfg(not(feature = "async"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_async {
+    ($($item:item)*) => {};
+}
+
+/// Expand to an invocation of a `tracing` macro only when the `instrument` feature is enabled.
+///
+/// `let $span = $macro!(...)` enters the span created by the macro until `$span` is dropped.
+#[cfg(feature = "instrument")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __instrument {
+    (let $span:ident = $macro:ident!($($arg:tt)*)) => {
+        let $span = $crate::__tracing::$macro!($($arg)*).entered();
+    };
+
+    ($macro:ident!($($arg:tt)*)) => {
+        $crate::__tracing::$macro!($($arg)*)
+    };
+}
+
+/// Expand to an invocation of a `tracing` macro only when the `instrument` feature is enabled.
+#[cfg(not(feature = "instrument"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __instrument {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(feature = "instrument")]
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/// Capture a backtrace of the current thread only when the `backtrace` feature is enabled and
+/// capturing is enabled by the environment.
+#[doc(hidden)]
+pub fn __capture_backtrace() -> Option<std::backtrace::Backtrace> {
+    #[cfg(feature = "backtrace")]
+    {
+        let backtrace = std::backtrace::Backtrace::capture();
+        if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            return Some(backtrace);
+        }
+    }
+    None
+}
+
+/// Implement an optional trait for a generated error type.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __reporter_derive {
+    (Clone, $ErrorName:ident < $T:ty >) => {
+        impl Clone for $ErrorName {
+            fn clone(&self) -> Self {
+                let mut chain = self.error.chain().rev();
+                let root = chain.next().map(ToString::to_string).unwrap_or_default();
+                let error = chain.fold(anyhow::Error::msg(root), |error, context| {
+                    error.context(context.to_string())
+                });
+
+                $ErrorName {
+                    error,
+                    extras: self.extras.clone(),
+                    retry: self.retry,
+                    reported_at: self.reported_at,
+                    reported: self.reported,
+                    severity: self.severity,
+                    thread_name: self.thread_name.clone(),
+                    thread_id: self.thread_id,
+                    location: self.location,
+                    backtrace: None,
+                    parent: self.parent,
+                }
+            }
+        }
+    };
+
+    (PartialEq, $ErrorName:ident < $T:ty >) => {
+        impl PartialEq for $ErrorName {
+            fn eq(&self, other: &Self) -> bool {
+                self.error
+                    .chain()
+                    .map(ToString::to_string)
+                    .eq(other.error.chain().map(ToString::to_string))
+                    && self.extras == other.extras
+                    && self.severity == other.severity
+            }
+        }
+    };
+
+    (Serialize, $ErrorName:ident < $T:ty >) => {
+        impl serde::Serialize for $ErrorName {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct(stringify!($ErrorName), 3)?;
+                state.serialize_field("error", &format!("{:#}", self.error))?;
+                state.serialize_field("extras", &self.extras)?;
+                state.serialize_field("severity", &format!("{:?}", self.severity))?;
+                state.end()
+            }
+        }
+    };
+
+    (ReporterExtra, $ErrorName:ident < $T:ty >) => {
+        impl $crate::ReporterExtra for $ErrorName {
+            type Extra = $T;
+
+            #[track_caller]
+            fn report_with_extra(error: anyhow::Error, extra: $T) -> slotmap::DefaultKey {
+                $ErrorName::report_with_extra(error, extra)
+            }
+
+            fn update(key: slotmap::DefaultKey, extra: $T) -> bool {
+                $ErrorName::update(key, extra)
+            }
         }
     };
 }
@@ -322,5 +3605,5 @@ macro_rules! make_reporter {
 ///
 /// The items in this module are automatically generated by [make_reporter!]
 pub mod example {
-    make_reporter!(ExampleReporter);
+    make_reporter!(ExampleReporter, derive(Clone, PartialEq, ReporterExtra));
 }
@@ -0,0 +1,18 @@
+#![cfg(feature = "backtrace")]
+
+error_report::make_reporter!(Traced);
+
+#[test]
+fn test() {
+    // read once, when the first backtrace is captured
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    let mut et = ErrorThread::default();
+    Traced::init(&mut et);
+
+    let key = report!("dang");
+
+    let errors = et.done();
+    let backtrace = errors[key].backtrace().unwrap().to_string();
+    assert!(backtrace.contains("backtrace::test"), "{backtrace}");
+}
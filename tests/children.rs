@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+error_report::make_reporter!(Tree);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Tree::init(&mut et);
+
+    let root = report!("operation failed");
+    let children = std::thread::scope(|scope| {
+        let threads = (0..3)
+            .map(|i| {
+                scope.spawn(move || {
+                    let child = report!(parent: root, "sub-operation {i} failed");
+                    for j in 0..2 {
+                        Tree::report_child(child, anyhow::anyhow!("step {i}.{j} failed"));
+                    }
+                    child
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut found = Tree::children(root);
+    found.sort();
+    let mut expected = children.clone();
+    expected.sort();
+    assert_eq!(found, expected);
+    assert_eq!(Tree::children(children[0]).len(), 2);
+
+    // removing a parent orphans its children
+    let orphaned = report!(parent: children[2], "late step");
+    assert!(Tree::remove(children[2]));
+
+    let errors = et.done();
+    assert_eq!(errors[root].parent(), None);
+    assert_eq!(errors[orphaned].parent(), Some(children[2]));
+    assert!(!errors.contains_key(children[2]));
+
+    let mut tree = HashMap::<_, Vec<_>>::new();
+    for (key, error) in &errors {
+        if let Some(parent) = error.parent() {
+            tree.entry(parent).or_default().push(key);
+        }
+    }
+    assert_eq!(tree[&root].len(), 2);
+    for child in &tree[&root] {
+        let steps = tree[child]
+            .iter()
+            .map(|step| errors[*step].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 2);
+        assert!(steps.iter().all(|step| step.starts_with("step")));
+    }
+}
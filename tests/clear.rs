@@ -0,0 +1,32 @@
+error_report::make_reporter!(Batched<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Batched::init(&mut et);
+
+    let first = report!("first batch");
+    let second = report!("first batch, again");
+    Batched::update(second, 2);
+
+    let drained = Batched::drain();
+    assert_eq!(drained.len(), 2);
+    assert_eq!(drained[0].0, first);
+    assert_eq!(drained[1].1.extra(), Some(&2));
+    assert!(Batched::is_empty());
+
+    // keys from before the drain don't address the errors which reuse their slots
+    let third = report!("second batch");
+    Batched::update(first, 1);
+    Batched::update(second, 1);
+    assert!(!Batched::contains(first));
+    assert_eq!(Batched::get(third).unwrap().extra, None);
+
+    Batched::clear();
+    assert!(!Batched::contains(third));
+    report!("third batch");
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.values().next().unwrap().to_string(), "third batch");
+}
@@ -0,0 +1,43 @@
+#![cfg(unix)]
+
+use {error_report::CommandExt, std::process::Command};
+
+error_report::make_reporter!(CommandError);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    CommandError::init(&mut et);
+
+    let output = Command::new("sh")
+        .args(["-c", "echo fine"])
+        .output_reported::<CommandError>()
+        .unwrap();
+    assert_eq!(output.stdout, b"fine\n");
+
+    let failed = Command::new("sh")
+        .args(["-c", "echo oops >&2; exit 3"])
+        .output_reported::<CommandError>()
+        .unwrap_err();
+    let truncated = Command::new("sh")
+        .args(["-c", "echo out; printf '%050d' 0 >&2; false"])
+        .output_reported_with_limit::<CommandError>(10)
+        .unwrap_err();
+    let missing = Command::new("this-program-does-not-exist")
+        .output_reported::<CommandError>()
+        .unwrap_err();
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(
+        errors[failed.key()].to_string(),
+        "\"sh\" \"-c\" \"echo oops >&2; exit 3\" failed with exit status: 3: stderr:\noops"
+    );
+    assert_eq!(
+        errors[truncated.key()].error().root_cause().to_string(),
+        "stdout:\nout\nstderr (truncated to the last 10 of 50 bytes):\n0000000000"
+    );
+    assert!(errors[missing.key()]
+        .to_string()
+        .starts_with("could not run \"this-program-does-not-exist\": "));
+}
@@ -0,0 +1,28 @@
+use slotmap::{DefaultKey, Key};
+
+error_report::make_reporter!(Tracked);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Tracked::init(&mut et);
+
+    let live = report!("live");
+    assert!(Tracked::contains(live));
+
+    let removed = Tracked::report_to_partition("spurious", anyhow::anyhow!("removed"));
+    assert!(Tracked::contains(removed));
+    Tracked::drain_partition("spurious");
+    assert!(!Tracked::contains(removed));
+
+    // the removed slot is reused, but the old key is still stale
+    let reused = report!("reused");
+    assert_ne!(reused, removed);
+    assert!(Tracked::contains(reused));
+    assert!(!Tracked::contains(removed));
+
+    assert!(!Tracked::contains(DefaultKey::null()));
+    assert!(!Tracked::contains(DefaultKey::default()));
+
+    assert_eq!(et.done().len(), 2);
+}
@@ -0,0 +1,31 @@
+use std::sync::Barrier;
+
+error_report::make_reporter!(Counted);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Counted::init(&mut et);
+    assert!(Counted::is_empty());
+
+    let barrier = Barrier::new(4);
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            let barrier = &barrier;
+            scope.spawn(move || {
+                for i in 0..25 {
+                    report!("thread {thread} error {i}");
+                    // this thread's errors so far are always included
+                    assert!(Counted::count() > i);
+                }
+                barrier.wait();
+                assert_eq!(Counted::count(), 100);
+            });
+        }
+    });
+
+    assert!(!Counted::is_empty());
+    report!("one more");
+    assert_eq!(Counted::count(), 101);
+    assert_eq!(et.done().len(), 101);
+}
@@ -0,0 +1,29 @@
+use error_report::{PausePolicy, PRE_INIT_CAPACITY};
+
+error_report::make_reporter!(Counted);
+
+#[test]
+fn test() {
+    for _ in 0..PRE_INIT_CAPACITY + 2 {
+        report!("early");
+    }
+
+    let mut et = ErrorThread::default();
+    assert_eq!(et.dropped(), 2);
+    Counted::init(&mut et);
+
+    Counted::add_middleware(|error| (!error.to_string().contains("filtered")).then_some(error));
+    report!("filtered");
+    Counted::pause(PausePolicy::Discard);
+    report!("discarded");
+    Counted::resume();
+    report!("stored");
+
+    // the last report has been fully processed once this returns
+    Counted::keys();
+    assert_eq!(et.dropped(), 4);
+    assert_eq!(et.stored(), PRE_INIT_CAPACITY + 1);
+    assert!(et.processed() >= 6);
+
+    assert_eq!(et.done().len(), PRE_INIT_CAPACITY + 1);
+}
@@ -0,0 +1,45 @@
+use error_report::Severity;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Extra {
+    attempts: u32,
+}
+
+error_report::make_reporter!(Derived<Extra>, derive(Clone, PartialEq, Serialize));
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Derived::init(&mut et);
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let key = Derived::report(anyhow::Error::new(io).context("could not open config"));
+    Derived::update(key, Extra { attempts: 3 });
+    let plain = report!("plain");
+    let warning = Derived::report_with_severity(anyhow::anyhow!("plain"), Severity::Warning);
+
+    let errors = et.done();
+    assert_ne!(errors[plain], errors[warning]);
+    assert_eq!(
+        serde_json::to_value(&errors[warning]).unwrap()["severity"],
+        "Warning"
+    );
+    let original = &errors[key];
+    let cloned = original.clone();
+
+    assert_eq!(&cloned, original);
+    assert_eq!(format!("{cloned:#}"), format!("{original:#}"));
+    assert_eq!(cloned.error().chain().count(), 2);
+    assert!(original.error().downcast_ref::<std::io::Error>().is_some());
+    assert!(cloned.error().downcast_ref::<std::io::Error>().is_none());
+
+    let json = serde_json::to_value(original).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "error": "could not open config: no such file",
+            "extras": [{ "attempts": 3 }],
+            "severity": "Error",
+        })
+    );
+}
@@ -0,0 +1,44 @@
+error_report::make_reporter!(Oops<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Oops::init(&mut et);
+
+    let key = report!("plain");
+    Oops::update(key, 5);
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    Oops::report(anyhow::Error::new(io).context("could not open config"));
+
+    let errors = et.done();
+    let mut rendered = errors
+        .values()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    rendered.sort();
+    assert_eq!(
+        rendered,
+        ["could not open config: no such file", "plain (5)"]
+    );
+
+    let (plain, io): (Vec<_>, Vec<_>) = errors
+        .into_iter()
+        .map(|(_, error)| error)
+        .partition(|error| error.extra().is_some());
+
+    let boxed = plain
+        .into_iter()
+        .map(|error| Box::new(error) as Box<dyn std::error::Error>)
+        .collect::<Vec<_>>();
+    assert_eq!(boxed[0].to_string(), "plain (5)");
+
+    // the chain is already in the message, so it isn't repeated as a source
+    let io = io.into_iter().next().unwrap();
+    assert!(std::error::Error::source(&io).is_none());
+    let wrapped = anyhow::Error::new(io).context("while starting");
+    assert_eq!(
+        format!("{wrapped:#}"),
+        "while starting: could not open config: no such file"
+    );
+}
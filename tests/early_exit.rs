@@ -0,0 +1,41 @@
+error_report::make_reporter!(Incomplete);
+
+fn step(fail: bool) -> Result<(), ()> {
+    let guard = Incomplete::on_early_exit("step did not complete");
+    if fail {
+        return Err(());
+    }
+    guard.disarm();
+    Ok(())
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Incomplete::init(&mut et);
+
+    step(false).unwrap();
+    step(true).unwrap_err();
+
+    let line = line!() + 2;
+    std::thread::spawn(|| {
+        let _guard = Incomplete::on_early_exit("thread did not complete");
+        panic!("oh no");
+    })
+    .join()
+    .unwrap_err();
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0]
+        .starts_with("step did not complete: scope exited early at tests/early_exit.rs:4:"));
+    assert_eq!(
+        messages[1],
+        format!("thread did not complete: scope unwound at tests/early_exit.rs:{line}:22")
+    );
+
+    // dropping a guard must not panic, even once the reporter is finished
+    step(true).unwrap_err();
+}
@@ -0,0 +1,40 @@
+error_report::make_reporter!(Staged<String>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Staged::init(&mut et);
+
+    let key = report!("failed", extra: String::from("parse"));
+    std::thread::scope(|scope| {
+        for stage in ["fetch", "store"] {
+            scope.spawn(move || {
+                for i in 0..10 {
+                    Staged::append_extra(key, format!("{stage} {i}"));
+                }
+            });
+        }
+    });
+
+    let replaced = report!("replaced");
+    Staged::append_extra(replaced, String::from("first"));
+    Staged::append_extra(replaced, String::from("second"));
+    Staged::update(replaced, String::from("only"));
+
+    let errors = et.done();
+    let extras = errors[key].extras();
+    assert_eq!(extras.len(), 21);
+    assert_eq!(extras[0], "parse");
+    assert_eq!(errors[key].extra(), extras.last());
+    for stage in ["fetch", "store"] {
+        // each thread's extras arrive in the order it sent them
+        let from_stage = extras
+            .iter()
+            .filter(|extra| extra.starts_with(stage))
+            .cloned()
+            .collect::<Vec<_>>();
+        let expected = (0..10).map(|i| format!("{stage} {i}")).collect::<Vec<_>>();
+        assert_eq!(from_stage, expected);
+    }
+    assert_eq!(errors[replaced].extras(), ["only"]);
+}
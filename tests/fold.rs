@@ -0,0 +1,42 @@
+error_report::make_reporter!(Folded<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Folded::init(&mut et);
+
+    let background = std::thread::spawn(|| {
+        for i in 0..200 {
+            report!("background {i}");
+        }
+    });
+
+    // every error reported before the fold is counted, even while another thread is reporting
+    for i in 0..20 {
+        report!("foreground {i}", extra: i);
+        let foreground = Folded::fold(0, |count, error| {
+            count + error.to_string().starts_with("foreground") as usize
+        });
+        assert_eq!(foreground, i as usize + 1);
+    }
+    background.join().unwrap();
+
+    let count = Folded::fold(0, |count, _| count + 1);
+    let with_extra = Folded::fold(0, |count, error| count + error.extra().is_some() as usize);
+    let joined = Folded::fold(String::new(), |mut joined, error| {
+        if let Some(extra) = error.extra() {
+            joined += &extra.to_string();
+        }
+        joined
+    });
+
+    let errors = et.done();
+    assert_eq!(count, errors.len());
+    assert_eq!(with_extra, 20);
+    let expected = errors
+        .values()
+        .filter_map(|error| error.extra())
+        .map(ToString::to_string)
+        .collect::<String>();
+    assert_eq!(joined, expected);
+}
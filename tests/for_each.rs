@@ -0,0 +1,48 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+error_report::make_reporter!(Counted<usize>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Counted::init(&mut et);
+
+    let workers = (0..2)
+        .map(|worker| {
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    report!("worker {worker} error {i}", extra: 0);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&count);
+    Counted::for_each(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let mut next = 0;
+    Counted::for_each_mut(move |error| {
+        *error.extra_mut().unwrap() = next;
+        next += 1;
+    });
+
+    let errors = et.done();
+    assert_eq!(count.load(Ordering::Relaxed), errors.len());
+    assert_eq!(errors.len(), 100);
+
+    let mut numbers = errors
+        .values()
+        .map(|error| *error.extra().unwrap())
+        .collect::<Vec<_>>();
+    numbers.sort();
+    assert_eq!(numbers, (0..100).collect::<Vec<_>>());
+}
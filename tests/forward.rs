@@ -0,0 +1,46 @@
+use error_report::Severity;
+
+error_report::make_reporter!(Parent, thread = ParentThread, macro = report_parent);
+error_report::make_reporter!(Child<u32>, thread = ChildThread, macro = report_child);
+
+#[test]
+fn test() {
+    let mut parent = ParentThread::default();
+    Parent::init(&mut parent);
+    let mut child = ChildThread::default();
+    Child::init(&mut child);
+
+    Child::add_middleware(|error| (!error.to_string().contains("private")).then_some(error));
+    Child::forward_to::<Parent>("child");
+
+    report_parent!("from the parent");
+    let key = report_child!("from the child", extra: 1);
+    report_child!("private");
+    error_report::warn_report!(Child, "warning from the child");
+
+    let parent = parent.done();
+    let mut messages = parent
+        .values()
+        .map(|error| (error.to_string(), error.severity()))
+        .collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(
+        messages,
+        [
+            (String::from("child: from the child"), Severity::Error),
+            (
+                String::from("child: warning from the child"),
+                Severity::Warning
+            ),
+            (String::from("from the parent"), Severity::Error),
+        ]
+    );
+
+    // the parent has finished, but the child keeps working
+    let late = report_child!("after the parent");
+    assert!(Child::contains(late));
+
+    let child = child.done();
+    assert_eq!(child.len(), 3);
+    assert_eq!(child[key].extra(), Some(&1));
+}
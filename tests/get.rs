@@ -0,0 +1,43 @@
+use slotmap::{DefaultKey, Key};
+
+error_report::make_reporter!(Inspected<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Inspected::init(&mut et);
+
+    assert!(Inspected::get(DefaultKey::null()).is_none());
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let key = Inspected::report(anyhow::Error::new(io).context("could not open config"));
+    Inspected::update(key, 5);
+
+    let snapshot = Inspected::get(key).unwrap();
+    assert_eq!(snapshot.message, "could not open config: no such file");
+    assert!(snapshot
+        .chain
+        .starts_with("could not open config\n\nCaused by:\n    no such file"));
+    assert_eq!(snapshot.extra, Some(5));
+
+    // snapshot the same error while other threads keep reporting
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            scope.spawn(move || {
+                for i in 0..50 {
+                    report!("thread {thread} error {i}");
+                }
+            });
+        }
+        for _ in 0..50 {
+            assert_eq!(Inspected::get(key).unwrap(), snapshot);
+        }
+    });
+
+    let removed = Inspected::report_to_partition("spurious", anyhow::anyhow!("removed"));
+    assert!(Inspected::get(removed).is_some());
+    Inspected::drain_partition("spurious");
+    assert!(Inspected::get(removed).is_none());
+
+    assert_eq!(et.done().len(), 201);
+}
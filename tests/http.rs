@@ -0,0 +1,25 @@
+use {error_report::HttpContext, std::time::Duration};
+
+error_report::make_reporter!(HandlerError<HttpContext>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    HandlerError::init(&mut et);
+
+    let full = report!(
+        "upstream unavailable",
+        extra: HttpContext::new("POST", "/orders")
+            .with_status(502)
+            .with_duration(Duration::from_millis(250))
+            .with_request_id("req-1")
+    );
+    let bare = report!("handler panicked", extra: HttpContext::new("GET", "/"));
+
+    let errors = et.done();
+    let full = errors[full].extra().unwrap();
+    assert_eq!(full.status, Some(502));
+    assert_eq!(full.request_id.as_deref(), Some("req-1"));
+    assert_eq!(full.to_string(), "POST /orders 502 250ms req-1");
+    assert_eq!(errors[bare].extra().unwrap().to_string(), "GET /");
+}
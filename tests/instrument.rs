@@ -0,0 +1,72 @@
+#![cfg(feature = "instrument")]
+
+use {
+    std::{fmt::Debug, sync::Mutex},
+    tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    },
+};
+
+error_report::make_reporter!(Traced);
+
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Records the message of every event.
+struct Messages;
+
+impl Visit for Messages {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            MESSAGES.lock().unwrap().push(format!("{value:?}"));
+        }
+    }
+}
+
+impl Subscriber for Messages {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        event.record(&mut Messages);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test() {
+    tracing::subscriber::set_global_default(Messages).unwrap();
+
+    let mut et = ErrorThread::default();
+    Traced::init(&mut et);
+    Traced::add_middleware(|error| (!error.to_string().contains("filtered")).then_some(error));
+    report!("stored");
+    report!("filtered");
+    et.done();
+
+    assert_eq!(
+        *MESSAGES.lock().unwrap(),
+        [
+            "message received",
+            "message received",
+            "error stored",
+            "message received",
+            "error filtered out by middleware",
+            "message received",
+            "error collector thread finished",
+        ]
+    );
+}
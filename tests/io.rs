@@ -0,0 +1,68 @@
+use {
+    error_report::{ReportedReader, ReportedWriter},
+    std::io::{self, Read, Write},
+};
+
+error_report::make_reporter!(IoError);
+
+/// A reader which fails after producing a fixed amount of data.
+struct Flaky(usize);
+
+impl Read for Flaky {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0 == 0 {
+            return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+        }
+        let n = buf.len().min(self.0);
+        buf[..n].fill(b'x');
+        self.0 -= n;
+        Ok(n)
+    }
+}
+
+/// A writer which fails once it has accepted a fixed amount of data.
+struct Full(usize);
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.0 == 0 {
+            return Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"));
+        }
+        let n = buf.len().min(self.0);
+        self.0 -= n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    IoError::init(&mut et);
+
+    let mut reader = ReportedReader::<IoError, _>::new(Flaky(10));
+    let mut data = Vec::new();
+    let error = reader.read_to_end(&mut data).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+    assert_eq!(reader.offset(), 10);
+
+    let mut writer = ReportedWriter::<IoError, _>::new(Full(4));
+    writer.write_all(b"ok").unwrap();
+    let error = writer.write_all(b"too long").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+    assert_eq!(writer.offset(), 4);
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(
+        messages,
+        [
+            "read failed at byte 10: reset",
+            "write failed at byte 4: disk full",
+        ]
+    );
+}
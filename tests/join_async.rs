@@ -0,0 +1,25 @@
+#![cfg(feature = "async")]
+
+error_report::make_reporter!(Shutdown);
+
+#[tokio::test]
+async fn test() {
+    let mut et = ErrorThread::default();
+    Shutdown::init(&mut et);
+
+    let workers = (0..4)
+        .map(|i| {
+            std::thread::spawn(move || {
+                for j in 0..100 {
+                    report!("worker {i} error {j}");
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let errors = et.join_async().await;
+    assert_eq!(errors.len(), 400);
+}
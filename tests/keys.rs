@@ -0,0 +1,31 @@
+error_report::make_reporter!(Listed<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Listed::init(&mut et);
+    assert!(Listed::keys().is_empty());
+
+    let threads = (0..4)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                (0..25)
+                    .map(|i| report!("thread {thread} error {i}"))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut reported = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+
+    let mut keys = Listed::keys();
+    reported.sort();
+    keys.sort();
+    assert_eq!(keys, reported);
+
+    let errors = et.done();
+    assert!(keys.iter().all(|key| errors.contains_key(*key)));
+    assert_eq!(keys.len(), errors.len());
+}
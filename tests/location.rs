@@ -0,0 +1,77 @@
+use error_report::{Reported, ResultExt};
+
+error_report::make_reporter!(Located, derive(ReporterExtra));
+
+fn propagate() -> Result<i32, Reported<Located>> {
+    Ok("dang".parse::<i32>()?)
+}
+
+fn bail() -> Result<(), Reported<Located>> {
+    error_report::bail_report!(Located, "bailed");
+}
+
+fn ensure() -> Result<(), Reported<Located>> {
+    error_report::ensure_report!(Located, 1 + 1 == 3);
+    Ok(())
+}
+
+fn guarded() {
+    let _guard = Located::on_early_exit("dropped");
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Located::init(&mut et);
+
+    let line = line!();
+    let from_macro = report!("from the macro");
+    let from_fn = Located::report(anyhow::anyhow!("from the function"));
+    let batch = Located::report_batch([anyhow::anyhow!("batched")]);
+    let partition = Located::partition("p");
+    let from_partition = partition.report(anyhow::anyhow!("partitioned"));
+    let from_warn = error_report::warn_report!(Located, "warned");
+
+    // helpers record where they were used, not somewhere inside the library
+    let propagated = propagate().unwrap_err().key();
+    let bailed = bail().unwrap_err().key();
+    let ensured = ensure().unwrap_err().key();
+    let context_line = line!() + 1;
+    let with_context = "dang".parse::<i32>().with_report_context::<Located>(|| ());
+    assert!(with_context.is_err());
+    guarded();
+
+    let errors = et.done();
+    let lines = [from_macro, from_fn, batch[0], from_partition, from_warn]
+        .into_iter()
+        .map(|key| {
+            let location = errors[key].location();
+            assert_eq!(location.file(), file!());
+            location.line()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [line + 1, line + 2, line + 3, line + 5, line + 6]);
+    assert_eq!(errors[from_macro].location().column(), 22);
+    assert!(format!("{:?}", errors[from_fn]).contains("location: Location"));
+
+    let location = |key| {
+        let location = errors[key].location();
+        assert_eq!(location.file(), file!());
+        location.line()
+    };
+    assert_eq!(location(propagated), 6);
+    assert_eq!(location(bailed), 10);
+    assert_eq!(location(ensured), 14);
+
+    let reported = [from_macro, from_fn, batch[0], from_partition, from_warn];
+    let mut others = errors
+        .iter()
+        .filter(|(key, _)| ![propagated, bailed, ensured].contains(key) && !reported.contains(key))
+        .map(|(_, error)| error.location())
+        .collect::<Vec<_>>();
+    others.sort_by_key(|location| location.line());
+    assert_eq!(others.len(), 2);
+    assert!(others.iter().all(|location| location.file() == file!()));
+    assert_eq!(others[0].line(), 19);
+    assert_eq!(others[1].line(), context_line);
+}
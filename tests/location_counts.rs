@@ -0,0 +1,39 @@
+error_report::make_reporter!(Callsites);
+
+fn flaky(i: usize) {
+    report!("flaky operation {i} failed");
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Callsites::init(&mut et);
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            scope.spawn(move || {
+                for i in 0..5 {
+                    flaky(thread * 5 + i);
+                }
+            });
+        }
+    });
+    let line = line!();
+    report!("once");
+    report!("twice");
+    report!("twice");
+
+    let counts = Callsites::count_by_location();
+    let summary = counts
+        .iter()
+        .map(|count| (count.location.line(), count.count))
+        .collect::<Vec<_>>();
+    // every use of report! is its own call site, even with the same message
+    assert_eq!(
+        summary,
+        [(4, 20), (line + 1, 1), (line + 2, 1), (line + 3, 1)]
+    );
+    assert!(counts.iter().all(|count| count.location.file() == file!()));
+
+    et.done();
+}
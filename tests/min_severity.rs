@@ -0,0 +1,36 @@
+use {error_report::Severity, slotmap::Key};
+
+error_report::make_reporter!(Filtered);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Filtered::init(&mut et);
+
+    Filtered::report_with_severity(anyhow::anyhow!("info"), Severity::Info);
+    Filtered::set_min_severity(Severity::Warning);
+    let info = Filtered::report_with_severity(anyhow::anyhow!("quiet"), Severity::Info);
+    let warning = error_report::warn_report!(Filtered, "warning");
+    assert!(info.is_null());
+    assert!(!warning.is_null());
+
+    // raising the minimum does not affect errors already stored
+    Filtered::set_min_severity(Severity::Fatal);
+    report!("error");
+    Filtered::report_with_severity(anyhow::anyhow!("fatal"), Severity::Fatal);
+
+    Filtered::set_min_severity(Severity::Info);
+    Filtered::report_with_severity(anyhow::anyhow!("loud again"), Severity::Info);
+
+    let counts = Filtered::count_by_severity();
+    assert_eq!(
+        (counts.info, counts.warning, counts.error, counts.fatal),
+        (2, 1, 0, 1)
+    );
+    assert_eq!(et.dropped(), 2);
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, ["fatal", "info", "loud again", "warning"]);
+}
@@ -0,0 +1,43 @@
+error_report::make_reporter!(JobError<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    JobError::init(&mut et);
+
+    let first = JobError::partition("job-1");
+    let second = JobError::partition("job-2");
+    assert_eq!(first.name(), "job-1");
+
+    let a = first.report(anyhow::anyhow!("a"));
+    second.report(anyhow::anyhow!("b"));
+    let c = first.report(anyhow::anyhow!("c"));
+    JobError::update(c, 3);
+    report!("global");
+
+    let drained = first.drain();
+    let keys = drained.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+    assert_eq!(keys, [a, c]);
+    assert_eq!(drained[1].1.extra(), Some(&3));
+    assert_eq!(JobError::keys().len(), 2);
+
+    // draining again finds nothing, but the partition can still be used
+    assert!(JobError::drain_partition("job-1").is_empty());
+    JobError::report_to_partition("job-1", anyhow::anyhow!("d"));
+    assert_eq!(JobError::drain_partition("job-1").len(), 1);
+
+    // errors removed some other way leave their partition
+    let removed = JobError::report_to_partition("job-3", anyhow::anyhow!("removed"));
+    let retained = JobError::report_to_partition("job-3", anyhow::anyhow!("retained"));
+    JobError::report_to_partition("job-3", anyhow::anyhow!("dropped"));
+    assert!(JobError::remove(removed));
+    JobError::retain(|error| error.to_string() != "dropped");
+    let drained = JobError::drain_partition("job-3");
+    assert_eq!(drained.len(), 1);
+    assert_eq!(drained[0].0, retained);
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, ["b", "global"]);
+}
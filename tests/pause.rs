@@ -0,0 +1,34 @@
+use {error_report::PausePolicy, slotmap::Key};
+
+error_report::make_reporter!(Paused);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Paused::init(&mut et);
+
+    report!("before");
+
+    Paused::pause(PausePolicy::Discard);
+    assert!(report!("discarded 1").is_null());
+    assert!(report!("discarded 2").is_null());
+    assert_eq!(Paused::discarded(), 2);
+
+    Paused::pause(PausePolicy::Buffer);
+    assert!(report!("buffered").is_null());
+    assert_eq!(Paused::keys().len(), 1);
+
+    Paused::resume();
+    assert_eq!(Paused::keys().len(), 2);
+    report!("after");
+
+    Paused::pause(PausePolicy::Buffer);
+    report!("buffered at exit");
+
+    let errors = et.done();
+    let messages = errors
+        .values()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["before", "buffered", "after", "buffered at exit"]);
+}
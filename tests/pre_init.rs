@@ -0,0 +1,28 @@
+use slotmap::Key;
+
+error_report::make_reporter!(Early<&'static str>);
+
+#[test]
+fn test() {
+    let early = report!("bad config");
+    assert!(early.is_null());
+    Early::report_with_extra(anyhow::anyhow!("no logger"), "stderr only");
+
+    for i in 0..error_report::PRE_INIT_CAPACITY {
+        report!("overflow {i}");
+    }
+
+    let mut et = ErrorThread::default();
+    Early::init(&mut et);
+
+    let late = report!("after init");
+    assert!(!late.is_null());
+
+    let errors = et.done();
+    assert_eq!(errors.len(), error_report::PRE_INIT_CAPACITY + 1);
+
+    let mut first = errors.values().take(2).map(ToString::to_string);
+    assert_eq!(first.next().unwrap(), "bad config");
+    assert_eq!(first.next().unwrap(), "no logger (\"stderr only\")");
+    assert_eq!(errors[late].to_string(), "after init");
+}
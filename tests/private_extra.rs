@@ -0,0 +1,26 @@
+use error_report::Reported;
+
+// not public, so the generated error type must not name it in a public trait
+#[derive(Debug, PartialEq)]
+struct Extra(u32);
+
+error_report::make_reporter!(Private<Extra>);
+
+fn parse(s: &str) -> Result<u32, Reported<Private>> {
+    Ok(s.parse()?)
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Private::init(&mut et);
+
+    let reported = parse("dang").unwrap_err();
+    assert!(Private::update(reported.key(), Extra(1)));
+    let attached = report!("attached", extra: Extra(2));
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[reported.key()].extra(), Some(&Extra(1)));
+    assert_eq!(errors[attached].extra(), Some(&Extra(2)));
+}
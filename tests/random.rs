@@ -13,13 +13,13 @@ fn test() {
     Idk::init(&mut et);
 
     let (tx, rx) = flume::unbounded();
-    let make_thread = |tx: Sender<()>| {
-        return move || {
+    let make_thread = |tx: flume::Sender<()>| {
+        move || {
             for i in 0..NUM_ERRORS_PER_THREAD {
                 report!(format!("{i}"));
                 tx.send(()).unwrap();
             }
-        };
+        }
     };
 
     let mut threads = Vec::new();
@@ -44,7 +44,8 @@ fn test() {
 
         if prev_num_chars != num_chars_int || num_reports == 1 {
             let percent = (ratio * 100.0) as usize;
-            print!("\r{num_reports}/{TOTAL_ERRORS} {percent:3}% {bar}");
+            let queued = Idk::progress().queued;
+            print!("\r{num_reports}/{TOTAL_ERRORS} {percent:3}% {bar} {queued:3} queued");
             std::io::stdout().flush().unwrap();
         }
         prev_num_chars = num_chars_int;
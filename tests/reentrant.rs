@@ -0,0 +1,28 @@
+use {error_report::CollectorState, slotmap::Key};
+
+error_report::make_reporter!(Nested);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Nested::init(&mut et);
+
+    report!("outer");
+    let (keys_tx, keys_rx) = flume::bounded(1);
+    Nested::for_each(move |error| {
+        // reporting from the error collector thread queues the error instead of waiting for it
+        let key = report!("while looking at {error}");
+        let batch = Nested::report_batch([anyhow::anyhow!("batched")]);
+        keys_tx.send([key, batch[0]]).unwrap();
+    });
+
+    // the errors were queued before the keys were sent
+    assert!(keys_rx.recv().unwrap().iter().all(|key| key.is_null()));
+    assert_eq!(Nested::count(), 3);
+    assert_eq!(Nested::status().state, CollectorState::Running);
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, ["batched", "outer", "while looking at outer"]);
+}
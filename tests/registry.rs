@@ -0,0 +1,36 @@
+use error_report::{registry, ErrorReporter};
+
+error_report::make_reporter!(Network<u16>, derive(ReporterExtra));
+error_report::make_reporter!(Storage, thread = StorageThread, macro = report_storage, derive(ReporterExtra));
+
+#[test]
+fn test() {
+    assert!(registry::names().is_empty());
+    assert!(registry::get("network").is_none());
+
+    assert!(registry::register::<Network>("network"));
+    assert!(registry::register::<Storage>("storage"));
+    // a name can only be registered once, even by the same reporter
+    assert!(!registry::register::<Storage>("network"));
+    assert!(!registry::register::<Network>("network"));
+    assert_eq!(registry::names(), ["network", "storage"]);
+
+    let mut network = ErrorThread::default();
+    Network::init(&mut network);
+    let mut storage = StorageThread::default();
+    Storage::init(&mut storage);
+
+    // the first registration under a name is kept
+    let reporter: &dyn ErrorReporter = registry::get("network").unwrap();
+    let key = reporter.report(anyhow::anyhow!("connection reset"));
+    reporter.update(key, Box::new(443_u16)).unwrap();
+    registry::get("storage")
+        .unwrap()
+        .report(anyhow::anyhow!("disk full"));
+    report_storage!("direct");
+
+    let network = network.done();
+    assert_eq!(network.len(), 1);
+    assert_eq!(network[key].extra(), Some(&443));
+    assert_eq!(storage.done().len(), 2);
+}
@@ -0,0 +1,41 @@
+error_report::make_reporter!(Spurious<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Spurious::init(&mut et);
+
+    let key = report!("retried");
+    assert!(Spurious::remove(key));
+    assert!(!Spurious::remove(key));
+    assert!(Spurious::get(key).is_none());
+
+    // the removed key stays dead even once its slot is reused
+    let reused = report!("reused");
+    Spurious::update(key, 1);
+    assert_eq!(Spurious::get(reused).unwrap().extra, None);
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            scope.spawn(move || {
+                for i in 0..50 {
+                    let key = report!("thread {thread} error {i}");
+                    if i % 5 == 0 {
+                        assert!(Spurious::remove(key));
+                        Spurious::update(key, i);
+                        assert!(!Spurious::contains(key));
+                    } else {
+                        Spurious::update(key, i);
+                    }
+                }
+            });
+        }
+    });
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 1 + 4 * 50 - 4 * 10);
+    assert!(errors
+        .values()
+        .filter_map(|error| error.extra())
+        .all(|i| i % 5 != 0));
+}
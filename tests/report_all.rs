@@ -0,0 +1,27 @@
+use slotmap::Key;
+
+error_report::make_reporter!(Invalid);
+
+#[test]
+fn test() {
+    // batches reported before init are buffered like single errors
+    let early = Invalid::report_batch([anyhow::anyhow!("early")]);
+    assert!(early[0].is_null());
+
+    let mut et = ErrorThread::default();
+    Invalid::init(&mut et);
+
+    let inputs = ["1", "a", "2", "b", "3"];
+    let numbers = Invalid::report_all(inputs.iter().map(|input| input.parse::<u32>()));
+    assert_eq!(numbers, [1, 2, 3]);
+
+    let none: Vec<u32> = Invalid::report_all(Vec::<Result<u32, anyhow::Error>>::new());
+    assert!(none.is_empty());
+
+    let keys = Invalid::report_batch([anyhow::anyhow!("first"), anyhow::anyhow!("second")]);
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 5);
+    assert_eq!(errors[keys[0]].to_string(), "first");
+    assert_eq!(errors[keys[1]].to_string(), "second");
+}
@@ -0,0 +1,52 @@
+use {
+    anyhow::anyhow,
+    slotmap::{DefaultKey, Key},
+    std::{sync::Barrier, thread},
+};
+
+error_report::make_reporter!(Upsert<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Upsert::init(&mut et);
+
+    // a null key always reports a new error
+    let first = Upsert::report_or_update(DefaultKey::null(), anyhow!("first"), 1);
+    assert!(!first.is_null());
+
+    // a stored key keeps its error and only takes the new extra
+    assert_eq!(
+        Upsert::report_or_update(first, anyhow!("ignored"), 2),
+        first
+    );
+
+    // a key which is no longer stored reports the new error
+    let removed = report!("removed");
+    Upsert::remove(removed);
+    let replacement = Upsert::report_or_update(removed, anyhow!("replacement"), 3);
+    assert_ne!(replacement, removed);
+
+    // concurrent callers merging into the same error never duplicate it
+    let barrier = Barrier::new(4);
+    thread::scope(|scope| {
+        for extra in 10..14 {
+            let barrier = &barrier;
+            scope.spawn(move || {
+                barrier.wait();
+                assert_eq!(
+                    Upsert::report_or_update(first, anyhow!("racing"), extra),
+                    first
+                );
+            });
+        }
+    });
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[first].error().to_string(), "first");
+    assert!((10..14).contains(&errors[first].extras()[0]));
+    assert_eq!(errors[first].extras().len(), 1);
+    assert_eq!(errors[replacement].error().to_string(), "replacement");
+    assert_eq!(errors[replacement].extras(), [3]);
+}
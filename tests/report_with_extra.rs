@@ -0,0 +1,58 @@
+error_report::make_reporter!(Attached<usize>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Attached::init(&mut et);
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            scope.spawn(move || {
+                for i in 0..100 {
+                    report!("thread {thread} error {i}", extra: i);
+                }
+            });
+        }
+
+        // a panic here would stop the error collector thread, failing the rest of the test
+        for _ in 0..50 {
+            Attached::for_each(|error| {
+                assert!(error.extra().is_some(), "{error:?}");
+            });
+        }
+    });
+
+    assert_eq!(Attached::fold(0, |n, error| n + error.extras().len()), 400);
+
+    // format arguments may be combined with extra information, with or without a parent
+    let (thread, i) = (4, 0);
+    let positional = report!("thread {} error {}", thread, i, extra: 1);
+    let trailing = report!("thread {} error {}", thread, i + 1, extra: 2,);
+    let child = report!(parent: positional, "step {}", i, extra: 3);
+    let child_inline = report!(parent: positional, "step {i}", extra: 4);
+
+    // without extra, any number of format arguments work like they do with anyhow::anyhow!
+    let many = report!(
+        "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9
+    );
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 405);
+    assert_eq!(
+        errors[many].error().to_string(),
+        "0123456789012345678901234567890123456789012345678901234567890123456789"
+    );
+    assert!(errors
+        .iter()
+        .all(|(key, error)| key == many || error.extra().is_some()));
+    assert_eq!(errors[positional].error().to_string(), "thread 4 error 0");
+    assert_eq!(errors[positional].extras(), [1]);
+    assert_eq!(errors[trailing].error().to_string(), "thread 4 error 1");
+    assert_eq!(errors[trailing].extras(), [2]);
+    assert_eq!(errors[child].error().to_string(), "step 0");
+    assert_eq!(errors[child].extras(), [3]);
+    assert_eq!(errors[child].parent(), Some(positional));
+    assert_eq!(errors[child_inline].extras(), [4]);
+    assert_eq!(errors[child_inline].parent(), Some(positional));
+}
@@ -0,0 +1,36 @@
+use error_report::Reported;
+
+error_report::make_reporter!(Propagated<&'static str>, derive(ReporterExtra));
+
+fn read_config(path: &str) -> Result<String, Reported<Propagated>> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+fn load() -> Result<u32, Reported<Propagated>> {
+    let config = read_config("/this/path/does/not/exist")?;
+    Ok(config.trim().parse()?)
+}
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Propagated::init(&mut et);
+
+    let reported = load().unwrap_err();
+    assert!(reported.update("while loading"));
+
+    let parsed = "nope".parse::<u32>().map_err(Reported::<Propagated>::from);
+    let parsed = parsed.unwrap_err();
+    assert!(parsed.to_string().contains("invalid digit"));
+
+    // a stale key is reported back to the caller
+    assert!(Propagated::remove(parsed.key()));
+    assert!(!parsed.update("too late"));
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[reported.key()].to_string(),
+        format!("{reported} (\"while loading\")")
+    );
+}
@@ -0,0 +1,22 @@
+error_report::make_reporter!(Pruned<bool>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Pruned::init(&mut et);
+
+    report!("disk full");
+    report!("connection reset");
+    report!("disk quota exceeded");
+    let recovered = report!("disk busy");
+    report!("timed out");
+    Pruned::update(recovered, true);
+
+    Pruned::retain(|error| error.to_string().contains("disk"));
+    Pruned::retain_with_key(move |key, error| key != recovered && error.extra().is_none());
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, ["disk full", "disk quota exceeded"]);
+}
@@ -0,0 +1,49 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+error_report::make_reporter!(Recent);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Recent::init(&mut et);
+
+    let archived = Arc::new(Mutex::new(Vec::new()));
+    let archive = Arc::clone(&archived);
+    Recent::set_archive(move |_, error| archive.lock().unwrap().push(error.to_string()));
+
+    report!("before retention");
+    Recent::set_retention(None);
+    Recent::set_retention(Some(Duration::from_millis(250)));
+    report!("old");
+    std::thread::sleep(Duration::from_millis(300));
+    let recent = report!("recent");
+
+    let keys = Recent::keys();
+    assert_eq!(keys, [recent]);
+    assert_eq!(et.evicted(), 2);
+    assert_eq!(*archived.lock().unwrap(), ["before retention", "old"]);
+
+    // expired errors are not observed even if nothing has been reported since
+    let expiring = report!("expiring");
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(!Recent::contains(expiring));
+    assert!(!Recent::contains(recent));
+    assert_eq!(Recent::fold(0, |n, _| n + 1), 0);
+    assert_eq!(et.evicted(), 4);
+
+    // without a window, nothing more is evicted
+    Recent::set_retention(None);
+    std::thread::sleep(Duration::from_millis(300));
+    report!("also kept");
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(messages, ["also kept"]);
+    assert!(errors
+        .values()
+        .all(|error| error.reported_at() <= std::time::SystemTime::now()));
+}
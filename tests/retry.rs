@@ -0,0 +1,45 @@
+use {
+    error_report::{Retry, RetryOutcome},
+    std::time::{Duration, SystemTime},
+};
+
+error_report::make_reporter!(Flaky);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Flaky::init(&mut et);
+
+    let recovered = report!("connection refused");
+    let abandoned = report!("connection reset");
+    let untouched = report!("bad request");
+
+    let next_retry = SystemTime::now() + Duration::from_secs(1);
+    Flaky::record_attempt(recovered, 1, Some(3), Some(next_retry));
+    Flaky::record_attempt(recovered, 2, Some(3), None);
+    Flaky::record_outcome(recovered, RetryOutcome::Succeeded);
+
+    Flaky::record_attempt(abandoned, 1, None, Some(next_retry));
+    Flaky::record_outcome(abandoned, RetryOutcome::GaveUp);
+
+    let errors = et.done();
+    assert_eq!(
+        errors[recovered].retry(),
+        Some(&Retry {
+            attempt: 2,
+            max_attempts: Some(3),
+            next_retry: None,
+            outcome: Some(RetryOutcome::Succeeded),
+        })
+    );
+    assert_eq!(
+        errors[abandoned].retry(),
+        Some(&Retry {
+            attempt: 1,
+            max_attempts: None,
+            next_retry: None,
+            outcome: Some(RetryOutcome::GaveUp),
+        })
+    );
+    assert_eq!(errors[untouched].retry(), None);
+}
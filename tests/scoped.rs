@@ -0,0 +1,41 @@
+error_report::make_reporter!(ScopedError<usize>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    ScopedError::init(&mut et);
+
+    let inputs = ["1", "two", "3", "four"];
+    let parsed = std::thread::scope(|scope| {
+        let handles = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                scope.spawn(move || match input.parse::<u32>() {
+                    Ok(n) => Some(n),
+                    Err(error) => {
+                        let key = report!("{input}: {error}");
+                        ScopedError::update(key, i);
+                        None
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(parsed, [1, 3]);
+
+    let (tx, rx) = flume::unbounded();
+    ScopedError::for_each(move |error| {
+        tx.send(*error.extra().unwrap()).unwrap();
+    });
+    let mut indices = rx.iter().collect::<Vec<_>>();
+    indices.sort();
+    assert_eq!(indices, [1, 3]);
+
+    assert_eq!(et.done().len(), 2);
+}
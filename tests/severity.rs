@@ -0,0 +1,54 @@
+use error_report::{Severity, SeverityCounts};
+
+error_report::make_reporter!(Graded);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Graded::init(&mut et);
+
+    let plain = report!("plain");
+    let fatal = Graded::report_with_severity(anyhow::anyhow!("fatal"), Severity::Fatal);
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            scope.spawn(move || {
+                for i in 0..10 {
+                    match i % 3 {
+                        0 => error_report::warn_report!(Graded, "thread {thread} warning {i}"),
+                        1 => Graded::report_with_severity(anyhow::anyhow!("note"), Severity::Info),
+                        _ => report!("thread {thread} error {i}"),
+                    };
+                }
+            });
+        }
+    });
+
+    let counts = Graded::count_by_severity();
+    assert_eq!(
+        counts,
+        SeverityCounts {
+            info: 12,
+            warning: 16,
+            error: 13,
+            fatal: 1,
+        }
+    );
+    assert_eq!(counts.get(Severity::Warning), 16);
+    assert_eq!(counts.total(), Graded::count());
+
+    let errors = et.done();
+    assert_eq!(errors[plain].severity(), Severity::Error);
+    assert_eq!(errors[fatal].severity(), Severity::Fatal);
+    assert_eq!(
+        errors
+            .values()
+            .map(Graded::severity)
+            .collect::<SeverityCounts>(),
+        counts
+    );
+    assert!(errors
+        .values()
+        .filter(|error| error.severity() == Severity::Warning)
+        .all(|error| error.to_string().contains("warning")));
+}
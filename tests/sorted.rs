@@ -0,0 +1,31 @@
+use error_report::Severity;
+
+error_report::make_reporter!(Ordered);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Ordered::init(&mut et);
+
+    let info = Ordered::report_with_severity(anyhow::anyhow!("info"), Severity::Info);
+    let first = report!("first error");
+    let fatal = Ordered::report_with_severity(anyhow::anyhow!("fatal"), Severity::Fatal);
+    let warning = error_report::warn_report!(Ordered, "warning");
+    let second = report!("second error");
+
+    // removing and reporting again reuses a slot, which must not affect the order
+    Ordered::remove(first);
+    let last = report!("last error");
+
+    let errors = et.done();
+    let keys =
+        |sorted: Vec<(_, &Ordered)>| sorted.into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(
+        keys(Ordered::sorted_by_time(&errors)),
+        [info, fatal, warning, second, last]
+    );
+    assert_eq!(
+        keys(Ordered::sorted_by_severity(&errors)),
+        [fatal, second, last, warning, info]
+    );
+}
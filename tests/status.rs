@@ -0,0 +1,35 @@
+use error_report::{CollectorState, PausePolicy};
+
+error_report::make_reporter!(Health);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Health::init(&mut et);
+
+    let status = Health::status();
+    assert_eq!(status.state, CollectorState::Running);
+    assert_eq!(status.last_processed, None);
+
+    // the first report has been fully processed once the second returns
+    report!("first");
+    report!("second");
+    let status = Health::status();
+    assert!(status.last_processed.is_some());
+    assert!(status.uptime <= Health::status().uptime);
+
+    Health::pause(PausePolicy::Buffer);
+    report!("buffered");
+    assert_eq!(
+        Health::status().state,
+        CollectorState::Paused(PausePolicy::Buffer)
+    );
+
+    Health::resume();
+    report!("resumed");
+    assert_eq!(Health::status().state, CollectorState::Running);
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 4);
+    assert_eq!(Health::status().state, CollectorState::Dead);
+}
@@ -0,0 +1,42 @@
+use std::thread;
+
+error_report::make_reporter!(Workers);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Workers::init(&mut et);
+
+    let workers = (0..4)
+        .map(|worker| {
+            thread::Builder::new()
+                .name(format!("worker-{worker}"))
+                .spawn(move || {
+                    // worker 3 produces most of the failures
+                    let failures = if worker == 3 { 9 } else { 1 };
+                    for i in 0..failures {
+                        report!("worker {worker} failure {i}");
+                    }
+                    thread::current().id()
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let ids = workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap())
+        .collect::<Vec<_>>();
+
+    let counts = Workers::count_by_thread();
+    assert_eq!(counts.len(), 4);
+    assert_eq!(counts[0].id, ids[3]);
+    assert_eq!(counts[0].name.as_deref(), Some("worker-3"));
+    assert_eq!(counts[0].count, 9);
+    assert!(counts[1..].iter().all(|count| count.count == 1));
+    assert_eq!(
+        counts.iter().map(|count| count.count).sum::<usize>(),
+        Workers::count()
+    );
+
+    et.done();
+}
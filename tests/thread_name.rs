@@ -0,0 +1,36 @@
+error_report::make_reporter!(Worker);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Worker::init(&mut et);
+
+    let workers = (0..4)
+        .map(|i| {
+            std::thread::Builder::new()
+                .name(format!("worker-{i}"))
+                .spawn(move || {
+                    let id = std::thread::current().id();
+                    (id, report!("dang: {i}"))
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let unnamed = std::thread::spawn(|| report!("unnamed")).join().unwrap();
+    let batch = Worker::report_batch([anyhow::anyhow!("batched")]);
+
+    let reported = workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap())
+        .collect::<Vec<_>>();
+
+    let errors = et.done();
+    for (i, (id, key)) in reported.into_iter().enumerate() {
+        let expected = format!("worker-{i}");
+        assert_eq!(errors[key].thread_name(), Some(expected.as_str()));
+        assert_eq!(errors[key].thread_id(), id);
+        assert!(format!("{:?}", errors[key]).contains(&expected));
+    }
+    assert_eq!(errors[unnamed].thread_name(), None);
+    assert_eq!(errors[batch[0]].thread_id(), std::thread::current().id());
+}
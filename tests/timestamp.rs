@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+error_report::make_reporter!(Stamped);
+
+#[test]
+fn test() {
+    report!("before init");
+
+    let mut et = ErrorThread::default();
+    Stamped::init(&mut et);
+
+    let first = report!("first");
+    std::thread::sleep(Duration::from_millis(20));
+    let second = report!("second");
+
+    let errors = et.done();
+    let early = errors
+        .iter()
+        .find(|(_, error)| error.to_string() == "before init")
+        .map(|(key, _)| key)
+        .unwrap();
+    assert!(errors[second].reported_at() > errors[first].reported_at());
+    assert!(
+        errors[second].elapsed_since_init()
+            >= errors[first].elapsed_since_init() + Duration::from_millis(20)
+    );
+    assert!(errors[early].reported_at() <= errors[first].reported_at());
+    assert_eq!(errors[early].elapsed_since_init(), Duration::ZERO);
+
+    let debug = format!("{:?}", errors[first]);
+    assert!(debug.contains("reported_at: SystemTime"), "{debug}");
+}
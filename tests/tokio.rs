@@ -0,0 +1,33 @@
+#![cfg(feature = "tokio")]
+
+error_report::make_reporter!(TaskError);
+
+#[tokio::test]
+async fn test() {
+    let mut et = ErrorThread::default();
+    TaskError::init(&mut et);
+
+    let ok = error_report::spawn_reported::<TaskError, _, anyhow::Error>("ok", async { Ok(5) });
+    let failed = error_report::spawn_reported::<TaskError, (), _>("failed", async {
+        "nope".parse::<i32>().map(|_| ())
+    });
+    let panicked =
+        error_report::spawn_reported::<TaskError, (), anyhow::Error>("panicked", async {
+            panic!("oh no")
+        });
+
+    assert_eq!(ok.await.unwrap(), Some(5));
+    assert_eq!(failed.await.unwrap(), None);
+    assert_eq!(panicked.await.unwrap(), None);
+
+    let errors = et.done();
+    let mut messages = errors.values().map(ToString::to_string).collect::<Vec<_>>();
+    messages.sort();
+    assert_eq!(
+        messages,
+        [
+            "task failed failed: invalid digit found in string",
+            "task panicked panicked: oh no",
+        ]
+    );
+}
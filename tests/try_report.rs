@@ -0,0 +1,59 @@
+use {
+    error_report::ReportError,
+    std::{thread, time::Duration},
+};
+
+error_report::make_reporter!(Fallible<u32>);
+
+#[test]
+fn test() {
+    assert_eq!(
+        Fallible::try_report(anyhow::anyhow!("before init")),
+        Err(ReportError::NotInitialized)
+    );
+    assert_eq!(
+        Fallible::try_update(Default::default(), 1),
+        Err(ReportError::NotInitialized)
+    );
+
+    let mut et = ErrorThread::default();
+    Fallible::init(&mut et);
+
+    let key = report!(try: "after init").unwrap();
+    assert!(Fallible::try_update(key, 2).unwrap());
+
+    // report continuously while the collector shuts down
+    let reporters = (0..4)
+        .map(|_| {
+            thread::spawn(|| {
+                let mut attempts = 0;
+                let mut failures = Vec::new();
+                while failures.is_empty() {
+                    attempts += 1;
+                    if let Err(error) = report!(try: "during shutdown") {
+                        failures.push(error);
+                    }
+                }
+                (attempts, failures)
+            })
+        })
+        .collect::<Vec<_>>();
+    thread::sleep(Duration::from_millis(10));
+
+    let errors = et.done();
+    let mut attempts = 1;
+    let mut failed = 0;
+    for reporter in reporters {
+        let (count, failures) = reporter.join().unwrap();
+        assert!(failures.iter().all(|error| *error == ReportError::ShutDown));
+        attempts += count;
+        failed += failures.len();
+    }
+    // every attempt is either stored or reported as failed, never both or neither
+    assert_eq!(errors.len() + failed, attempts);
+    assert!(errors.len() > 1);
+    assert_eq!(errors[key].extra(), Some(&2));
+
+    assert_eq!(report!(try: "after done"), Err(ReportError::ShutDown));
+    assert_eq!(Fallible::try_update(key, 3), Err(ReportError::ShutDown));
+}
@@ -0,0 +1,40 @@
+use error_report::ReportError;
+
+error_report::make_reporter!(Checked<u32>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Checked::init(&mut et);
+
+    let live = report!("live");
+    assert!(Checked::update(live, 1));
+
+    let removed = report!("removed");
+    Checked::remove(removed);
+    assert!(!Checked::update(removed, 2));
+
+    let cleared = report!("cleared");
+    Checked::clear();
+    let reused = report!("reused");
+    assert!(!Checked::update(cleared, 3));
+    assert!(!Checked::update(live, 4));
+
+    Checked::update_detached(reused, 5);
+    Checked::update_detached(cleared, 6);
+    assert_eq!(Checked::try_update(cleared, 7), Ok(false));
+
+    // on the error collector thread, updates are queued instead of waited for
+    let (queued_tx, queued_rx) = flume::bounded(1);
+    Checked::for_each(move |_| {
+        assert!(!Checked::update(reused, 8));
+        assert_eq!(Checked::try_update(reused, 9), Err(ReportError::Reentrant));
+        queued_tx.send(()).unwrap();
+    });
+    queued_rx.recv().unwrap();
+    assert_eq!(Checked::try_update(reused, 5), Ok(true));
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[reused].extras(), [5]);
+}
@@ -0,0 +1,48 @@
+#[derive(Debug, Default, PartialEq)]
+pub struct Attempts {
+    count: u32,
+    last: String,
+}
+
+error_report::make_reporter!(Counter<Attempts>);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Counter::init(&mut et);
+
+    let key = report!("flaky");
+    for i in 0..5 {
+        Counter::update_with(key, move |extra| {
+            let attempts = extra.get_or_insert_with(Default::default);
+            attempts.count += 1;
+            attempts.last = format!("attempt {i}");
+        });
+    }
+
+    let cleared = report!(
+        "recovered",
+        extra: Attempts {
+            count: 1,
+            last: String::new(),
+        }
+    );
+    Counter::update_with(cleared, |extra| *extra = None);
+
+    // a stale key doesn't call the function
+    let stale = report!("removed");
+    Counter::remove(stale);
+    Counter::update_with(stale, |_| panic!("called with a stale key"));
+
+    let errors = et.done();
+    assert_eq!(
+        errors[key].extra(),
+        Some(&Attempts {
+            count: 5,
+            last: String::from("attempt 4"),
+        })
+    );
+    assert_eq!(errors[key].extras().len(), 1);
+    assert_eq!(errors[cleared].extra(), None);
+    assert_eq!(errors.len(), 2);
+}
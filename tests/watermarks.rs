@@ -0,0 +1,37 @@
+use {
+    error_report::Watermark,
+    std::sync::{Arc, Mutex},
+};
+
+error_report::make_reporter!(Backlogged<usize>);
+
+#[test]
+fn test() {
+    let crossings = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&crossings);
+    Backlogged::set_watermarks(10, 2, move |watermark, queued| {
+        recorded.lock().unwrap().push((watermark, queued));
+    });
+
+    let mut et = ErrorThread::default();
+    Backlogged::init(&mut et);
+    let key = report!("dang");
+
+    // block the collector so that updates pile up behind it
+    let (release_tx, release_rx) = flume::bounded::<()>(0);
+    Backlogged::for_each(move |_| release_rx.recv().unwrap());
+    for i in 0..20 {
+        Backlogged::update_detached(key, i);
+    }
+    assert_eq!(*crossings.lock().unwrap(), [(Watermark::High, 10)]);
+
+    release_tx.send(()).unwrap();
+    assert_eq!(Backlogged::fold(0, |_, error| *error.extra().unwrap()), 19);
+
+    let crossings = crossings.lock().unwrap();
+    assert_eq!(crossings.len(), 2);
+    assert!(matches!(crossings[1], (Watermark::Low, queued) if queued <= 2));
+    drop(crossings);
+
+    et.done();
+}
@@ -0,0 +1,36 @@
+use error_report::ReportError;
+
+error_report::make_reporter!(Shared);
+
+#[test]
+fn test() {
+    let mut et = ErrorThread::default();
+    Shared::init(&mut et);
+
+    let kept = report!("keep me");
+    let dropped = report!("drop me");
+
+    assert!(Shared::with_errors(
+        move |errors| errors.contains_key(dropped)
+    ));
+    let removed = Shared::with_errors_mut(|errors| {
+        let before = errors.len();
+        errors.retain(|_, error| !error.to_string().starts_with("drop"));
+        before - errors.len()
+    });
+    assert_eq!(removed, 1);
+    assert_eq!(Shared::keys(), [kept]);
+
+    // reporting from the collector thread fails instead of deadlocking
+    let (result_tx, result_rx) = flume::bounded(1);
+    Shared::for_each(move |_| {
+        let _ = result_tx.send(Shared::try_report(anyhow::anyhow!("reentrant")));
+    });
+    assert_eq!(result_rx.recv().unwrap(), Err(ReportError::Reentrant));
+
+    let key = Shared::with_errors(|_| report!(try: "also reentrant"));
+    assert_eq!(key, Err(ReportError::Reentrant));
+
+    let errors = et.done();
+    assert_eq!(errors.len(), 1);
+}